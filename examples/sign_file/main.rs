@@ -5,7 +5,9 @@ use cclang::{
     MachineBuilder,
     Mode,
     Script,
-    Signing
+    ScriptOutcome,
+    Signing,
+    outcome
 };
 use semver::VersionReq;
 use sodiumoxide::{
@@ -245,16 +247,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .script(&sig)
                 .version_req(&VersionReq::parse(format!(">= {}", VERSION).as_str()).unwrap())
                 .build();
-            let mut result = machine.execute(&FileIO).unwrap();
 
-            // the result should be a boolean with the value true
-            match result.pop() {
-                Some(CCLang::Boolean(true)) => {
+            // the result should be a single boolean with the value true
+            match outcome::run(&mut machine, &FileIO, true) {
+                ScriptOutcome::Success => {
                     println!("Signature is VALID.");
                     Ok(())
                 },
-                _ => {
+                ScriptOutcome::Failure => {
                     Err(Box::new(io::Error::new(io::ErrorKind::InvalidData, "Signature is INVALID")))
+                },
+                ScriptOutcome::Error(e) => {
+                    Err(Box::new(io::Error::new(io::ErrorKind::InvalidData, e)))
                 }
             }
         }
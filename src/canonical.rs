@@ -0,0 +1,187 @@
+use bytes::{
+    BufMut,
+    Bytes,
+    BytesMut
+};
+use crate::{
+    cclang::CCLangVisitor,
+    crypto::{
+        self,
+        CryptoProvider
+    },
+    multiformats::{
+        put_varint,
+        varint
+    },
+    CCLang,
+    Encoding,
+    Hashing,
+    Script
+};
+use serde::de::{
+    self,
+    Visitor
+};
+
+// The canonical form of a script is a sequence of typed records, one for each
+// token the script is written as:
+//
+//   record = type || length || value
+//
+// where type is one byte, length is an unsigned LEB128 varint and value is:
+//
+//   0x00 opcode        the opcode token, e.g. "DUP"
+//   0x01 Boolean       0x00 or 0x01
+//   0x02 Text          UTF-8
+//   0x03 Index         8 byte big-endian signed integer
+//   0x04 EncodingId    the encoding token, e.g. "Hex"
+//   0x05 EncryptionId  the encryption token
+//   0x06 SigningId     the signing token
+//   0x07 HashingId     the hashing token
+//   0x08 Whence        the whence token
+//   0x09 Mode          the mode token
+//
+// Unlike the text form, Text("12") and Index(12) have different records.
+// Binary data and lists are written as the tokens that create them, the same
+// as in the text form, so "ab Hex DECODE" and a Binary holding 0xab have the
+// same canonical form. Secrets, errors and handles have no canonical form.
+
+const OPCODE: u8 = 0x00;
+const BOOLEAN: u8 = 0x01;
+const TEXT: u8 = 0x02;
+const INDEX: u8 = 0x03;
+const ENCODING: u8 = 0x04;
+const ENCRYPTION: u8 = 0x05;
+const SIGNING: u8 = 0x06;
+const HASHING: u8 = 0x07;
+const WHENCE: u8 = 0x08;
+const MODE: u8 = 0x09;
+
+fn record(buf: &mut BytesMut, t: u8, value: &[u8]) {
+    buf.put_u8(t);
+    put_varint(buf, value.len() as u64);
+    buf.put_slice(value);
+}
+
+fn put(buf: &mut BytesMut, i: &CCLang) -> Result<(), String> {
+    match i {
+        CCLang::Boolean(b) => record(buf, BOOLEAN, &[*b as u8]),
+        CCLang::Binary(b) => {
+            record(buf, TEXT, hex::encode(b.as_ref()).as_bytes());
+            record(buf, ENCODING, Encoding::Hex.to_string().as_bytes());
+            record(buf, OPCODE, CCLang::Decode.to_string().as_bytes());
+        },
+        CCLang::Text(t) => record(buf, TEXT, t.as_bytes()),
+        CCLang::Index(n) => record(buf, INDEX, &(*n as i64).to_be_bytes()),
+        CCLang::EncodingId(e) => record(buf, ENCODING, e.to_string().as_bytes()),
+        CCLang::EncryptionId(e) => record(buf, ENCRYPTION, e.to_string().as_bytes()),
+        CCLang::SigningId(s) => record(buf, SIGNING, s.to_string().as_bytes()),
+        CCLang::HashingId(h) => record(buf, HASHING, h.to_string().as_bytes()),
+        CCLang::Whence(w) => record(buf, WHENCE, w.to_string().as_bytes()),
+        CCLang::Mode(m) => record(buf, MODE, m.to_string().as_bytes()),
+        CCLang::List(l) => {
+            for i in l {
+                put(buf, i)?;
+            }
+            put(buf, &CCLang::Index(l.len() as isize))?;
+            put(buf, &CCLang::Pack)?;
+        },
+        CCLang::Secret(_) |
+        CCLang::Error(_) |
+        CCLang::Handle(_) => {
+            return Err(format!("a {} has no canonical form", i.type_name()));
+        },
+        _ => record(buf, OPCODE, i.to_string().as_bytes())
+    }
+    Ok(())
+}
+
+/// The canonical form of the script
+pub fn encode(script: &Script<CCLang>) -> Result<Bytes, String> {
+    let mut buf = BytesMut::new();
+    let mut ip = 0;
+    while let Some(i) = script.get(ip) {
+        put(&mut buf, &i)?;
+        ip += 1;
+    }
+    Ok(buf.freeze())
+}
+
+// parse a token record, making sure it is the expected type of token
+fn token(value: &[u8], t: u8) -> Result<CCLang, String> {
+    let s = std::str::from_utf8(value).map_err(|_| "token is not valid UTF-8".to_string())?;
+    let i = CCLangVisitor.visit_str::<de::value::Error>(s).map_err(|e| e.to_string())?;
+    let ok = match t {
+        OPCODE => i.type_name() == "Opcode",
+        ENCODING => matches!(i, CCLang::EncodingId(_)),
+        ENCRYPTION => matches!(i, CCLang::EncryptionId(_)),
+        SIGNING => matches!(i, CCLang::SigningId(_)),
+        HASHING => matches!(i, CCLang::HashingId(_)),
+        WHENCE => matches!(i, CCLang::Whence(_)),
+        MODE => matches!(i, CCLang::Mode(_)),
+        _ => false
+    };
+    if !ok {
+        return Err(format!("{} is not a valid token for its record type", s));
+    }
+    Ok(i)
+}
+
+/// Parse a script from its canonical form. Anything that isn't exactly the
+/// canonical form of the script it parses to is rejected.
+pub fn decode(b: &[u8]) -> Result<Script<CCLang>, String> {
+    let mut items = Vec::new();
+    let mut rest = b;
+    while let Some((&t, r)) = rest.split_first() {
+        let (len, r) = varint(r)?;
+        if len > r.len() as u64 {
+            return Err("record is truncated".to_string());
+        }
+        let (value, r) = r.split_at(len as usize);
+        let i = match t {
+            BOOLEAN => match value {
+                [0] => CCLang::Boolean(false),
+                [1] => CCLang::Boolean(true),
+                _ => return Err("invalid Boolean record".to_string())
+            },
+            TEXT => match std::str::from_utf8(value) {
+                Ok(s) => CCLang::Text(s.to_string()),
+                Err(_) => return Err("Text record is not valid UTF-8".to_string())
+            },
+            INDEX => {
+                if value.len() != 8 {
+                    return Err("invalid Index record".to_string());
+                }
+                let mut n = [0u8; 8];
+                n.copy_from_slice(value);
+                CCLang::Index(i64::from_be_bytes(n) as isize)
+            },
+            _ => token(value, t)?
+        };
+        items.push(i);
+        rest = r;
+    }
+    let script = Script::from(items);
+    if encode(&script)?.as_ref() != b {
+        return Err("script is not in canonical form".to_string());
+    }
+    Ok(script)
+}
+
+/// Script identifiers
+pub trait ScriptId {
+    /// The hash of the canonical form of the script
+    fn id(&self, h: Hashing) -> Result<Bytes, String> {
+        self.id_with(&crypto::DEFAULT, h)
+    }
+
+    /// The hash of the canonical form of the script using the crypto provider
+    fn id_with(&self, p: &dyn CryptoProvider, h: Hashing) -> Result<Bytes, String>;
+}
+
+impl ScriptId for Script<CCLang> {
+    fn id_with(&self, p: &dyn CryptoProvider, h: Hashing) -> Result<Bytes, String> {
+        h.supported().map_err(|e| e.to_string())?;
+        Ok(h.digest_with(p, &encode(self)?))
+    }
+}
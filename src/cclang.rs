@@ -1,49 +1,97 @@
 use base64;
-use bs58;
 use bytes::{
-    BufMut,
     BytesMut,
     Bytes
 };
 use gsm::{
     AppIO,
     Instruction,
-    Machine
+    Machine,
+    Script
 };
 use hex;
+use crate::{
+    canonical,
+    crypto::{
+        self,
+        CryptoProvider
+    },
+    der,
+    merkle,
+    multiformats,
+    secret::{
+        memcmp,
+        Secret
+    },
+    shamir,
+    Context
+};
 use semver::Version;
 use serde::{
     de,
     Deserialize,
     Deserializer
 };
-use sodiumoxide::crypto::hash::{
-    sha256,
-    sha512
-};
-use sodiumoxide::crypto::secretbox::{
-    xsalsa20poly1305
-};
-use sodiumoxide::crypto::sign::{
-    PublicKey,
-    SecretKey,
-    Signature,
-    sign_detached,
-    verify_detached
-};
 use std::{
     any::Any,
     cmp::Ordering,
+    convert::TryFrom,
     fmt,
+    mem,
     rc::Rc
 };
 
+/// An algorithm that isn't built into this build of the crate. The tokens for
+/// every algorithm are always recognized but using one that needs a cargo
+/// feature that isn't enabled is an error.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnsupportedAlgorithm {
+    /// The algorithm token
+    pub algorithm: String,
+
+    /// The cargo feature that builds it in
+    pub feature: &'static str
+}
+
+impl UnsupportedAlgorithm {
+    pub(crate) fn new<A: fmt::Display>(algorithm: A, feature: &'static str) -> Self {
+        UnsupportedAlgorithm { algorithm: algorithm.to_string(), feature }
+    }
+}
+
+impl fmt::Display for UnsupportedAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} is not supported, it needs the {} feature", self.algorithm, self.feature)
+    }
+}
+
+impl std::error::Error for UnsupportedAlgorithm {}
+
+// check that the feature for the algorithm is enabled
+fn require<A: fmt::Display>(algorithm: A, feature: &'static str, enabled: bool) -> Result<(), UnsupportedAlgorithm> {
+    if enabled {
+        Ok(())
+    } else {
+        Err(UnsupportedAlgorithm::new(algorithm, feature))
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub enum Encoding {
     Hex,
     Base64,
     Base64Url,
+    Base64NoPad,
+    Base64UrlNoPad,
     Base58Bitcoin,
+    Base58Check,
+    Base32,
+    Base32Hex,
+    Base32Crockford,
+    Multibase,
+    Bech32,
+    Bech32m,
+    Pem,
 }
 
 struct EncodingVisitor;
@@ -61,7 +109,17 @@ impl<'de> de::Visitor<'de> for EncodingVisitor {
             "hex" => Ok(Encoding::Hex),
             "base64" => Ok(Encoding::Base64),
             "base64url" => Ok(Encoding::Base64Url),
+            "base64nopad" => Ok(Encoding::Base64NoPad),
+            "base64urlnopad" => Ok(Encoding::Base64UrlNoPad),
             "base58bitcoin" => Ok(Encoding::Base58Bitcoin),
+            "base58check" => Ok(Encoding::Base58Check),
+            "base32" => Ok(Encoding::Base32),
+            "base32hex" => Ok(Encoding::Base32Hex),
+            "base32crockford" => Ok(Encoding::Base32Crockford),
+            "multibase" => Ok(Encoding::Multibase),
+            "bech32" => Ok(Encoding::Bech32),
+            "bech32m" => Ok(Encoding::Bech32m),
+            "pem" => Ok(Encoding::Pem),
             &_ => Err(E::custom(format!("failed to parse '{}'", l)))
         }
     }
@@ -69,17 +127,359 @@ impl<'de> de::Visitor<'de> for EncodingVisitor {
 
 impl<'de> Deserialize<'de> for Encoding {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Encoding, D::Error> {
-        d.deserialize_any(EncodingVisitor)
+        let e = d.deserialize_any(EncodingVisitor)?;
+        e.supported().map_err(de::Error::custom)?;
+        Ok(e)
+    }
+}
+
+// bs58 is only needed for the Base58 encodings and for base58btc multibase
+#[cfg(feature = "encoding-base58")]
+mod base58 {
+    pub fn decode(s: &str) -> Option<Vec<u8>> {
+        bs58::decode(s).into_vec().ok()
+    }
+
+    pub fn decode_check(s: &str) -> Result<Vec<u8>, String> {
+        bs58::decode(s).with_check(None).into_vec()
+            .map_err(|e| format!("failed to decode Base58Check text: {}", e))
+    }
+
+    pub fn encode(b: &[u8]) -> String {
+        bs58::encode(b).into_string()
+    }
+
+    pub fn encode_check(b: &[u8]) -> String {
+        bs58::encode(b).with_check().into_string()
+    }
+}
+
+// the encodings that use these are rejected before they get here
+#[cfg(not(feature = "encoding-base58"))]
+mod base58 {
+    pub fn decode(_: &str) -> Option<Vec<u8>> {
+        unreachable!()
+    }
+
+    pub fn decode_check(_: &str) -> Result<Vec<u8>, String> {
+        unreachable!()
+    }
+
+    pub fn encode(_: &[u8]) -> String {
+        unreachable!()
+    }
+
+    pub fn encode_check(_: &[u8]) -> String {
+        unreachable!()
+    }
+}
+
+// data-encoding is only needed for the Base32 encodings and the base32
+// multibase prefixes
+#[cfg(feature = "encoding-base32")]
+mod base32 {
+    use data_encoding::{
+        Specification,
+        BASE32,
+        BASE32HEX,
+        BASE32HEX_NOPAD,
+        BASE32_NOPAD
+    };
+
+    // Crockford's Base32 alphabet. Decoding is case-insensitive, maps the easily
+    // confused I, L and O to 1, 1 and 0 and ignores hyphens.
+    fn crockford() -> data_encoding::Encoding {
+        let mut spec = Specification::new();
+        spec.symbols.push_str("0123456789ABCDEFGHJKMNPQRSTVWXYZ");
+        spec.translate.from.push_str("abcdefghjkmnpqrstvwxyzIiLlOo");
+        spec.translate.to.push_str("ABCDEFGHJKMNPQRSTVWXYZ111100");
+        spec.ignore.push('-');
+        spec.encoding().unwrap()
+    }
+
+    pub fn decode(s: &str, pad: bool) -> Option<Vec<u8>> {
+        if pad { BASE32.decode(s.as_bytes()).ok() } else { BASE32_NOPAD.decode(s.as_bytes()).ok() }
+    }
+
+    pub fn decode_hex(s: &str, pad: bool) -> Option<Vec<u8>> {
+        if pad { BASE32HEX.decode(s.as_bytes()).ok() } else { BASE32HEX_NOPAD.decode(s.as_bytes()).ok() }
+    }
+
+    pub fn decode_crockford(s: &str) -> Option<Vec<u8>> {
+        crockford().decode(s.as_bytes()).ok()
+    }
+
+    pub fn encode(b: &[u8]) -> String {
+        BASE32.encode(b)
+    }
+
+    pub fn encode_hex(b: &[u8]) -> String {
+        BASE32HEX.encode(b)
+    }
+
+    pub fn encode_crockford(b: &[u8]) -> String {
+        crockford().encode(b)
+    }
+}
+
+// the encodings that use these are rejected before they get here
+#[cfg(not(feature = "encoding-base32"))]
+mod base32 {
+    pub fn decode(_: &str, _: bool) -> Option<Vec<u8>> {
+        unreachable!()
+    }
+
+    pub fn decode_hex(_: &str, _: bool) -> Option<Vec<u8>> {
+        unreachable!()
+    }
+
+    pub fn decode_crockford(_: &str) -> Option<Vec<u8>> {
+        unreachable!()
+    }
+
+    pub fn encode(_: &[u8]) -> String {
+        unreachable!()
+    }
+
+    pub fn encode_hex(_: &[u8]) -> String {
+        unreachable!()
+    }
+
+    pub fn encode_crockford(_: &[u8]) -> String {
+        unreachable!()
     }
 }
 
+#[cfg(feature = "encoding-bech32")]
+mod bech32 {
+    use ::bech32::{
+        FromBase32,
+        ToBase32,
+        Variant
+    };
+    use super::Encoding;
+
+    fn variant(e: &Encoding) -> Variant {
+        match e {
+            Encoding::Bech32m => Variant::Bech32m,
+            _ => Variant::Bech32
+        }
+    }
+
+    pub fn decode(e: &Encoding, s: &str) -> Result<(String, Vec<u8>), String> {
+        let (hrp, data, v) = ::bech32::decode(s)
+            .map_err(|err| format!("failed to decode {} text: {}", e, err))?;
+        if v != variant(e) {
+            return Err(format!("failed to decode {} text: checksum is for {:?}", e, v));
+        }
+        let data = Vec::<u8>::from_base32(&data)
+            .map_err(|err| format!("failed to decode {} text: {}", e, err))?;
+        Ok((hrp, data))
+    }
+
+    pub fn encode(e: &Encoding, label: &str, b: &[u8]) -> Result<String, String> {
+        ::bech32::encode(label, b.to_base32(), variant(e))
+            .map_err(|err| format!("failed to encode {} text: {}", e, err))
+    }
+}
+
+// the encodings that use these are rejected before they get here
+#[cfg(not(feature = "encoding-bech32"))]
+mod bech32 {
+    use super::Encoding;
+
+    pub fn decode(_: &Encoding, _: &str) -> Result<(String, Vec<u8>), String> {
+        unreachable!()
+    }
+
+    pub fn encode(_: &Encoding, _: &str, _: &[u8]) -> Result<String, String> {
+        unreachable!()
+    }
+}
+
+impl Encoding {
+    /// Check that the encoding is built in
+    pub fn supported(&self) -> Result<(), UnsupportedAlgorithm> {
+        match self {
+            Encoding::Base58Bitcoin |
+            Encoding::Base58Check => require(self, "encoding-base58", cfg!(feature = "encoding-base58")),
+            Encoding::Base32 |
+            Encoding::Base32Hex |
+            Encoding::Base32Crockford => require(self, "encoding-base32", cfg!(feature = "encoding-base32")),
+            Encoding::Bech32 |
+            Encoding::Bech32m => require(self, "encoding-bech32", cfg!(feature = "encoding-bech32")),
+            _ => Ok(())
+        }
+    }
+
+    /// Decode the text to binary. Labeled encodings have the label dropped.
+    pub fn decode(&self, s: &str) -> Result<Vec<u8>, String> {
+        self.supported().map_err(|e| e.to_string())?;
+        let data = match self {
+            Encoding::Hex => hex::decode(s).ok(),
+            Encoding::Base64 => base64::decode_config(s, base64::STANDARD).ok(),
+            Encoding::Base64Url => base64::decode_config(s, base64::URL_SAFE).ok(),
+            Encoding::Base64NoPad => base64::decode_config(s, base64::STANDARD_NO_PAD).ok(),
+            Encoding::Base64UrlNoPad => base64::decode_config(s, base64::URL_SAFE_NO_PAD).ok(),
+            Encoding::Base58Bitcoin => base58::decode(s),
+            Encoding::Base58Check => return base58::decode_check(s),
+            Encoding::Base32 => base32::decode(s, true),
+            Encoding::Base32Hex => base32::decode_hex(s, true),
+            Encoding::Base32Crockford => base32::decode_crockford(s),
+            Encoding::Multibase => {
+                // the first character identifies the encoding of the rest
+                let mut chars = s.chars();
+                let prefix = chars.next();
+                let rest = chars.as_str();
+                match prefix {
+                    Some('f') | Some('F') => hex::decode(rest).ok(),
+                    Some(c @ 'b') | Some(c @ 'B') | Some(c @ 'c') | Some(c @ 'C') => {
+                        Encoding::Base32.supported().map_err(|e| e.to_string())?;
+                        base32::decode(&rest.to_uppercase(), c == 'c' || c == 'C')
+                    },
+                    Some(c @ 'v') | Some(c @ 'V') | Some(c @ 't') | Some(c @ 'T') => {
+                        Encoding::Base32Hex.supported().map_err(|e| e.to_string())?;
+                        base32::decode_hex(&rest.to_uppercase(), c == 't' || c == 'T')
+                    },
+                    Some('z') => {
+                        Encoding::Base58Bitcoin.supported().map_err(|e| e.to_string())?;
+                        base58::decode(rest)
+                    },
+                    Some('m') => base64::decode_config(rest, base64::STANDARD_NO_PAD).ok(),
+                    Some('M') => base64::decode_config(rest, base64::STANDARD).ok(),
+                    Some('u') => base64::decode_config(rest, base64::URL_SAFE_NO_PAD).ok(),
+                    Some('U') => base64::decode_config(rest, base64::URL_SAFE).ok(),
+                    _ => return Err("unknown multibase prefix".to_string())
+                }
+            },
+            Encoding::Bech32 |
+            Encoding::Bech32m |
+            Encoding::Pem => {
+                return self.decode_labeled(s).map(|(_, data)| data);
+            }
+        };
+        data.ok_or_else(|| format!("failed to decode {} text", self))
+    }
+
+    /// Encode the binary as text
+    pub fn encode(&self, b: &[u8]) -> Result<String, String> {
+        self.supported().map_err(|e| e.to_string())?;
+        match self {
+            Encoding::Hex => Ok(hex::encode(b)),
+            Encoding::Base64 => Ok(base64::encode_config(b, base64::STANDARD)),
+            Encoding::Base64Url => Ok(base64::encode_config(b, base64::URL_SAFE)),
+            Encoding::Base64NoPad => Ok(base64::encode_config(b, base64::STANDARD_NO_PAD)),
+            Encoding::Base64UrlNoPad => Ok(base64::encode_config(b, base64::URL_SAFE_NO_PAD)),
+            Encoding::Base58Bitcoin => Ok(base58::encode(b)),
+            Encoding::Base58Check => Ok(base58::encode_check(b)),
+            Encoding::Base32 => Ok(base32::encode(b)),
+            Encoding::Base32Hex => Ok(base32::encode_hex(b)),
+            Encoding::Base32Crockford => Ok(base32::encode_crockford(b)),
+            // multibase encodes with base58btc, the encoding used by DID documents
+            Encoding::Multibase => {
+                Encoding::Base58Bitcoin.supported().map_err(|e| e.to_string())?;
+                Ok(format!("z{}", base58::encode(b)))
+            },
+            Encoding::Bech32 |
+            Encoding::Bech32m |
+            Encoding::Pem => Err(format!("{} encoding requires a label", self))
+        }
+    }
+
+    /// The encoding named by the prefix of a multibase text. The padded and
+    /// unpadded forms of Base32 and Base32Hex are both reported as Base32 and
+    /// Base32Hex.
+    pub fn multibase_encoding(s: &str) -> Option<Encoding> {
+        match s.chars().next()? {
+            'f' | 'F' => Some(Encoding::Hex),
+            'b' | 'B' | 'c' | 'C' => Some(Encoding::Base32),
+            'v' | 'V' | 't' | 'T' => Some(Encoding::Base32Hex),
+            'z' => Some(Encoding::Base58Bitcoin),
+            'm' => Some(Encoding::Base64NoPad),
+            'M' => Some(Encoding::Base64),
+            'u' => Some(Encoding::Base64UrlNoPad),
+            'U' => Some(Encoding::Base64Url),
+            _ => None
+        }
+    }
+
+    /// True if the encoded text carries a label (e.g. the Bech32
+    /// human-readable part or the PEM type) along with the binary data
+    pub fn is_labeled(&self) -> bool {
+        match self {
+            Encoding::Bech32 | Encoding::Bech32m | Encoding::Pem => true,
+            _ => false
+        }
+    }
+
+    /// Decode the text to its label and binary
+    pub fn decode_labeled(&self, s: &str) -> Result<(String, Vec<u8>), String> {
+        self.supported().map_err(|e| e.to_string())?;
+        match self {
+            Encoding::Bech32 |
+            Encoding::Bech32m => bech32::decode(self, s),
+            Encoding::Pem => pem_decode(s).ok_or_else(|| format!("failed to decode {} text", self)),
+            _ => Err(format!("{} encoding has no label", self))
+        }
+    }
+
+    /// Encode the label and binary as text
+    pub fn encode_labeled(&self, label: &str, b: &[u8]) -> Result<String, String> {
+        self.supported().map_err(|e| e.to_string())?;
+        match self {
+            Encoding::Bech32 |
+            Encoding::Bech32m => bech32::encode(self, label, b),
+            Encoding::Pem => Ok(pem_encode(label, b)),
+            _ => Err(format!("{} encoding has no label", self))
+        }
+    }
+}
+
+// RFC 7468 textual encoding. Decoding is lax: the label is everything between
+// "-----BEGIN " and the closing dashes, whitespace inside of the base64 is
+// ignored and so is any text before the BEGIN line. Encoding is strict with
+// 64 character lines.
+fn pem_decode(s: &str) -> Option<(String, Vec<u8>)> {
+    let begin = s.find("-----BEGIN ")? + 11;
+    let rest = &s[begin..];
+    let end = rest.find("-----")?;
+    let label = &rest[..end];
+    let rest = &rest[end + 5..];
+    let footer = format!("-----END {}-----", label);
+    let body = &rest[..rest.find(&footer)?];
+    let b64: String = body.chars().filter(|c| !c.is_whitespace()).collect();
+    let data = base64::decode_config(&b64, base64::STANDARD).ok()?;
+    Some((label.to_string(), data))
+}
+
+fn pem_encode(label: &str, b: &[u8]) -> String {
+    let b64 = base64::encode_config(b, base64::STANDARD);
+    let mut s = format!("-----BEGIN {}-----\n", label);
+    for line in b64.as_bytes().chunks(64) {
+        s.push_str(std::str::from_utf8(line).unwrap());
+        s.push('\n');
+    }
+    s.push_str(&format!("-----END {}-----\n", label));
+    s
+}
+
 impl fmt::Display for Encoding {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Encoding::Hex       => write!(f, "Hex"),
             Encoding::Base64    => write!(f, "Base64"),
             Encoding::Base64Url => write!(f, "Base64Url"),
-            Encoding::Base58Bitcoin => write!(f, "Base58Bitcoin")
+            Encoding::Base64NoPad => write!(f, "Base64NoPad"),
+            Encoding::Base64UrlNoPad => write!(f, "Base64UrlNoPad"),
+            Encoding::Base58Bitcoin => write!(f, "Base58Bitcoin"),
+            Encoding::Base58Check => write!(f, "Base58Check"),
+            Encoding::Base32 => write!(f, "Base32"),
+            Encoding::Base32Hex => write!(f, "Base32Hex"),
+            Encoding::Base32Crockford => write!(f, "Base32Crockford"),
+            Encoding::Multibase => write!(f, "Multibase"),
+            Encoding::Bech32 => write!(f, "Bech32"),
+            Encoding::Bech32m => write!(f, "Bech32m"),
+            Encoding::Pem => write!(f, "Pem")
         }
     }
 }
@@ -109,7 +509,18 @@ impl<'de> de::Visitor<'de> for EncryptionVisitor {
 
 impl<'de> Deserialize<'de> for Encryption {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Encryption, D::Error> {
-        d.deserialize_any(EncryptionVisitor)
+        let e = d.deserialize_any(EncryptionVisitor)?;
+        e.supported().map_err(de::Error::custom)?;
+        Ok(e)
+    }
+}
+
+impl Encryption {
+    /// Check that the encryption algorithm is built in
+    pub fn supported(&self) -> Result<(), UnsupportedAlgorithm> {
+        match self {
+            Encryption::XSalsa20Poly1305 => require(self, "enc-xsalsa20", cfg!(feature = "enc-xsalsa20"))
+        }
     }
 }
 
@@ -123,7 +534,8 @@ impl fmt::Display for Encryption {
 
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub enum Signing {
-    Ed25519
+    Ed25519,
+    Secp256k1
 }
 
 struct SigningVisitor;
@@ -139,6 +551,7 @@ impl<'de> de::Visitor<'de> for SigningVisitor {
         let l = v.to_lowercase();
         match l.as_str() {
             "ed25519" => Ok(Signing::Ed25519),
+            "secp256k1" => Ok(Signing::Secp256k1),
             &_ => Err(E::custom(format!("failed to parse '{}'", l)))
         }
     }
@@ -146,14 +559,27 @@ impl<'de> de::Visitor<'de> for SigningVisitor {
 
 impl<'de> Deserialize<'de> for Signing {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Signing, D::Error> {
-        d.deserialize_any(SigningVisitor)
+        let s = d.deserialize_any(SigningVisitor)?;
+        s.supported().map_err(de::Error::custom)?;
+        Ok(s)
+    }
+}
+
+impl Signing {
+    /// Check that the signing algorithm is built in
+    pub fn supported(&self) -> Result<(), UnsupportedAlgorithm> {
+        match self {
+            Signing::Ed25519 => require(self, "sign-ed25519", cfg!(feature = "sign-ed25519")),
+            Signing::Secp256k1 => require(self, "sign-secp256k1", cfg!(feature = "sign-secp256k1"))
+        }
     }
 }
 
 impl fmt::Display for Signing {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Signing::Ed25519 => write!(f, "Ed25519")
+            Signing::Ed25519 => write!(f, "Ed25519"),
+            Signing::Secp256k1 => write!(f, "Secp256k1")
         }
     }
 }
@@ -162,6 +588,7 @@ impl fmt::Display for Signing {
 pub enum Hashing {
     SHA256,
     SHA512,
+    BLAKE3,
 }
 
 struct HashingVisitor;
@@ -178,6 +605,7 @@ impl<'de> de::Visitor<'de> for HashingVisitor {
         match l.as_str() {
             "sha256" => Ok(Hashing::SHA256),
             "sha512" => Ok(Hashing::SHA512),
+            "blake3" => Ok(Hashing::BLAKE3),
             &_ => Err(E::custom(format!("failed to parse '{}'", l)))
         }
     }
@@ -185,7 +613,40 @@ impl<'de> de::Visitor<'de> for HashingVisitor {
 
 impl<'de> Deserialize<'de> for Hashing {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Hashing, D::Error> {
-        d.deserialize_any(HashingVisitor)
+        let h = d.deserialize_any(HashingVisitor)?;
+        h.supported().map_err(de::Error::custom)?;
+        Ok(h)
+    }
+}
+
+impl Hashing {
+    /// Check that the hashing algorithm is built in
+    pub fn supported(&self) -> Result<(), UnsupportedAlgorithm> {
+        match self {
+            Hashing::SHA256 |
+            Hashing::SHA512 => require(self, "hash-sha2", cfg!(feature = "hash-sha2")),
+            Hashing::BLAKE3 => require(self, "hash-blake3", cfg!(feature = "hash-blake3"))
+        }
+    }
+
+    /// Hash the data with this hashing algorithm. Panics if the algorithm
+    /// isn't built in.
+    pub fn digest(&self, data: &[u8]) -> Bytes {
+        self.digest_with(&crypto::DEFAULT, data)
+    }
+
+    /// Hash the data with the crypto provider
+    pub fn digest_with(&self, p: &dyn CryptoProvider, data: &[u8]) -> Bytes {
+        p.hash(*self, data)
+    }
+
+    /// The size of the digest in bytes
+    pub fn size(&self) -> usize {
+        match self {
+            Hashing::SHA256 => 32,
+            Hashing::SHA512 => 64,
+            Hashing::BLAKE3 => 32
+        }
     }
 }
 
@@ -194,6 +655,7 @@ impl fmt::Display for Hashing {
         match self {
             Hashing::SHA256   => write!(f, "SHA256"),
             Hashing::SHA512   => write!(f, "SHA512"),
+            Hashing::BLAKE3   => write!(f, "BLAKE3"),
         }
     }
 }
@@ -211,6 +673,13 @@ pub enum CCLang
     EncryptionId(Encryption),
     SigningId(Signing),
     HashingId(Hashing),
+    List(Vec<CCLang>),
+
+    // secret key material
+    Secret(Secret),
+
+    // execution error, left on top of the stack when a script aborts
+    Error(String),
 
     // I/O data types
     Index(isize),
@@ -233,7 +702,20 @@ pub enum CCLang
     GreaterThan,
     GreaterThanEqual,
 
+    // integer arithmetic
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Min,
+    Max,
+    Abs,
+
     // logical operations
+    And,
+    Or,
+    Not,
     /*
     BitOr,
     BitAnd,
@@ -244,6 +726,8 @@ pub enum CCLang
     // conversion operations
     Encode,
     Decode,
+    ToBytes,
+    ToText,
 
     // encryption
     Encrypt,
@@ -252,22 +736,291 @@ pub enum CCLang
     // signing
     Sign,
     Verify,
+    UnwrapKey,
+    MultiKey,
+
+    // secret key material handling
+    ToSecret,
+    Reveal,
 
     // hashing
     Hash,
+    MerkleRoot,
+    MerkleVerify,
+    ToMultihash,
+    FromMultihash,
+    MultihashVerify,
+    CallHash,
+
+    // secret sharing
+    Split,
+    Combine,
 
     // data maniupation
     Concat,
     Slice,
 
+    // list operations
+    Pack,
+    Unpack,
+    Contains,
+    IntersectCount,
+    Unique,
+
     // stack operations
     Dup,
     Pop,
+    Swap,
+    Over,
+    Rot,
+    Nip,
+    Tuck,
+    Pick,
+    Roll,
+    Depth,
+    DropN,
+
+    // type introspection
+    Len,
+    TypeOf,
+    IsBinary,
+    IsText,
+    IsBoolean,
+    IsIndex,
+    IsSecret,
+
+    // host inputs
+    GetVar,
+
+    // time
+    Now,
+    CheckTime,
 
     // flow control
+    Assert,
     If,
     Else,
-    Fi
+    Fi,
+    Repeat,
+    Loop,
+    Return,
+    Halt,
+
+    // macros
+    Define,
+    EndDefine,
+    Invoke
+}
+
+// key material can be given either as a Binary or as a Secret
+enum Key {
+    Binary(Bytes),
+    Secret(Secret)
+}
+
+impl Key {
+    fn from(v: CCLang) -> Option<Key> {
+        match v {
+            CCLang::Binary(b) => Some(Key::Binary(b)),
+            CCLang::Secret(s) => Some(Key::Secret(s)),
+            _ => None
+        }
+    }
+}
+
+impl AsRef<[u8]> for Key {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Key::Binary(b) => b.as_ref(),
+            Key::Secret(s) => s.as_ref()
+        }
+    }
+}
+
+// stop the machine. clearing the return stack makes Machine::execute return
+// None, even from inside of an 'IF' block, and leaves the data stack as it is.
+fn halt(m: &mut Machine<CCLang>) {
+    while m.popr().is_some() {}
+}
+
+// arithmetic operands are Index values. Booleans count as 0 or 1 the same as
+// they do when compared to an Index so that valid signatures can be counted.
+fn to_index(v: CCLang) -> Option<isize> {
+    match v {
+        CCLang::Index(i) => Some(i),
+        CCLang::Boolean(b) => Some(b as isize),
+        _ => None
+    }
+}
+
+// pop two operands, apply the checked operation and push the result
+fn arithmetic<F>(ip: usize, m: &mut Machine<CCLang>, op: F)
+where
+    F: Fn(isize, isize) -> Result<isize, &'static str>
+{
+    if let Some(r) = m.pop().and_then(to_index) {
+        if let Some(l) = m.pop().and_then(to_index) {
+            match op(l, r) {
+                Ok(i) => {
+                    m.push(CCLang::Index(i));
+                    m.pushr(ip + 1);
+                },
+                Err(e) => fail(m, e)
+            }
+            return;
+        }
+    }
+    panic!()
+}
+
+// pop the top n items off of the stack and return them in stack order, deepest
+// first. if there aren't n items the stack is left as it was.
+fn take(m: &mut Machine<CCLang>, n: isize) -> Result<Vec<CCLang>, &'static str> {
+    if n < 0 {
+        return Err("item count must not be negative");
+    }
+    let mut items = Vec::new();
+    for _ in 0..n {
+        match m.pop() {
+            Some(i) => items.push(i),
+            None => {
+                while let Some(i) = items.pop() {
+                    m.push(i);
+                }
+                return Err("stack underflow");
+            }
+        }
+    }
+    items.reverse();
+    Ok(items)
+}
+
+// replace the top n items with the items at the given positions, deepest
+// first, where 0 is the deepest of the n items
+fn shuffle(ip: usize, m: &mut Machine<CCLang>, n: isize, order: &[usize]) {
+    match take(m, n) {
+        Ok(items) => {
+            for i in order {
+                m.push(items[*i].clone());
+            }
+            m.pushr(ip + 1);
+        },
+        Err(e) => fail(m, e)
+    }
+}
+
+// equality for the list opcodes. unlike '=' values of different types are
+// never equal, so a nonzero Index isn't TRUE, and a Handle is only equal to
+// itself.
+fn same(a: &CCLang, b: &CCLang) -> bool {
+    match (a, b) {
+        (CCLang::List(l), CCLang::List(r)) => {
+            l.len() == r.len() && l.iter().zip(r).all(|(l, r)| same(l, r))
+        },
+        (CCLang::Handle(l), CCLang::Handle(r)) => Rc::ptr_eq(l, r),
+        _ => mem::discriminant(a) == mem::discriminant(b) && a == b
+    }
+}
+
+// true if the item is in the list
+fn has(items: &[CCLang], item: &CCLang) -> bool {
+    items.iter().any(|i| same(i, item))
+}
+
+// remove the duplicate items from a list, keeping the first of each
+fn unique(items: Vec<CCLang>) -> Vec<CCLang> {
+    let mut u: Vec<CCLang> = Vec::new();
+    for i in items {
+        if !has(&u, &i) {
+            u.push(i);
+        }
+    }
+    u
+}
+
+// how deeply 'CALLHASH' scripts can be nested
+const MAX_CALL_DEPTH: usize = 8;
+
+// run the script on the stack of this machine. if the script stops early this
+// machine stops too, with whatever the script left on the stack. the script
+// runs with a copy of the context one call deeper.
+fn call(ip: usize, m: &mut Machine<CCLang>, io: &dyn AppIO<CCLang>, ctx: &Context, script: Script<CCLang>) {
+    if ctx.depth() >= MAX_CALL_DEPTH {
+        fail(m, "CALLHASH is nested too deeply");
+        return;
+    }
+    let ctx = ctx.called();
+
+    let mut items = Vec::new();
+    while let Some(i) = m.pop() {
+        items.push(i);
+    }
+    let mut inner = Machine::from(script);
+    while let Some(i) = items.pop() {
+        inner.push(i);
+    }
+    let finished = ctx.execute(&mut inner, io).is_some();
+    while let Some(i) = inner.pop() {
+        items.push(i);
+    }
+    while let Some(i) = items.pop() {
+        m.push(i);
+    }
+    if finished {
+        m.pushr(ip + 1);
+    } else {
+        halt(m);
+    }
+}
+
+// push an unwrapped key and its signing algorithm id
+fn push_key(ip: usize, m: &mut Machine<CCLang>, ctx: &Context, key: Result<der::Key, &'static str>) {
+    match key {
+        Ok(der::Key::Public(id, pk)) => {
+            if !permitted(m, ctx, &CCLang::SigningId(id)) {
+                return;
+            }
+            m.push(CCLang::Binary(Bytes::from(pk)));
+            m.push(CCLang::SigningId(id));
+            m.pushr(ip + 1);
+        },
+        Ok(der::Key::Private(id, sk)) => {
+            if !permitted(m, ctx, &CCLang::SigningId(id)) {
+                return;
+            }
+            m.push(CCLang::Secret(sk));
+            m.push(CCLang::SigningId(id));
+            m.pushr(ip + 1);
+        },
+        Err(e) => fail(m, e)
+    }
+}
+
+// fail unless the execution policy allows the instruction or the algorithm
+// it names, returning whether it does
+fn permitted(m: &mut Machine<CCLang>, ctx: &Context, i: &CCLang) -> bool {
+    let allowed = ctx.permits(i);
+    if !allowed {
+        fail(m, &format!("{} is not allowed by the execution policy", i));
+    }
+    allowed
+}
+
+// fail unless the algorithm an opcode is about to use is built in and allowed
+// by the execution policy. an id can reach an opcode without being pushed as a
+// literal, from 'GETVAR' for one, so it is checked again where it is used.
+fn usable(m: &mut Machine<CCLang>, ctx: &Context, id: CCLang) -> bool {
+    if let Err(e) = id.supported() {
+        fail(m, &e.to_string());
+        return false;
+    }
+    permitted(m, ctx, &id)
+}
+
+// abort execution, leaving the error on top of the data stack
+fn fail(m: &mut Machine<CCLang>, e: &str) {
+    m.push(CCLang::Error(e.to_string()));
+    halt(m);
 }
 
 struct IfMatch {
@@ -292,6 +1045,16 @@ fn find_matching_elsefi(m: &Machine<CCLang>, i: usize) -> Option<IfMatch> {
                 ip = im.fii + 1;
             },
 
+            Some(CCLang::Repeat) => {
+                // this is an inner 'REPEAT' so skip to just after its 'LOOP'
+                ip = find_matching_loop(m, ip)? + 1;
+            },
+
+            // a 'LOOP' before our 'FI' means the blocks overlap
+            Some(CCLang::Loop) => {
+                return None;
+            },
+
             Some(CCLang::Else) => {
                 // this is an 'ELSE' to our starting 'IF' so we just record the
                 // index in the result and move on.
@@ -320,7 +1083,44 @@ fn find_matching_elsefi(m: &Machine<CCLang>, i: usize) -> Option<IfMatch> {
     }
 }
 
-struct CCLangVisitor;
+fn find_matching_loop(m: &Machine<CCLang>, i: usize) -> Option<usize> {
+    let mut ip = i + 1;
+    loop {
+        match m.geti(ip) {
+            // skip over inner 'IF' and 'REPEAT' blocks
+            Some(CCLang::If) => {
+                ip = find_matching_elsefi(m, ip)?.fii + 1;
+            },
+            Some(CCLang::Repeat) => {
+                ip = find_matching_loop(m, ip)? + 1;
+            },
+
+            // we found our matching 'LOOP'
+            Some(CCLang::Loop) => {
+                return Some(ip);
+            },
+
+            // an 'ELSE' or 'FI' before our 'LOOP' means the blocks overlap
+            Some(CCLang::Else) | Some(CCLang::Fi) => {
+                return None;
+            },
+
+            Some(_) => {
+                ip += 1;
+            },
+
+            // reached the end of the script without finding the 'LOOP'
+            None => {
+                return None;
+            }
+        }
+    }
+}
+
+// how a Secret is displayed
+const REDACTED: &str = "SECRET(REDACTED)";
+
+pub(crate) struct CCLangVisitor;
 
 impl<'de> de::Visitor<'de> for CCLangVisitor {
     type Value = CCLang;
@@ -338,12 +1138,16 @@ impl<'de> de::Visitor<'de> for CCLangVisitor {
         let wv = gsm::WhenceVisitor;
 
         if let Ok(e) = encodingv.visit_str::<E>(v) {
+            e.supported().map_err(E::custom)?;
             return Ok(CCLang::EncodingId(e));
         } else if let Ok(e) = encryptionv.visit_str::<E>(v) {
+            e.supported().map_err(E::custom)?;
             return Ok(CCLang::EncryptionId(e));
         } else if let Ok(s) = signingv.visit_str::<E>(v) {
+            s.supported().map_err(E::custom)?;
             return Ok(CCLang::SigningId(s));
         } else if let Ok(h) = hashingv.visit_str::<E>(v) {
+            h.supported().map_err(E::custom)?;
             return Ok(CCLang::HashingId(h));
         } else if let Ok(m) = mv.visit_str::<E>(v) {
             return Ok(CCLang::Mode(m));
@@ -366,23 +1170,83 @@ impl<'de> de::Visitor<'de> for CCLangVisitor {
                 "<=" => return Ok(CCLang::LessThanEqual),
                 ">" => return Ok(CCLang::GreaterThan),
                 ">=" => return Ok(CCLang::GreaterThanEqual),
+                "add" => return Ok(CCLang::Add),
+                "sub" => return Ok(CCLang::Sub),
+                "mul" => return Ok(CCLang::Mul),
+                "div" => return Ok(CCLang::Div),
+                "mod" => return Ok(CCLang::Mod),
+                "min" => return Ok(CCLang::Min),
+                "max" => return Ok(CCLang::Max),
+                "abs" => return Ok(CCLang::Abs),
+                "and" => return Ok(CCLang::And),
+                "or" => return Ok(CCLang::Or),
+                "not" => return Ok(CCLang::Not),
                 "encode" => return Ok(CCLang::Encode),
                 "decode" => return Ok(CCLang::Decode),
+                "tobytes" => return Ok(CCLang::ToBytes),
+                "totext" => return Ok(CCLang::ToText),
                 "encrypt" => return Ok(CCLang::Encrypt),
                 "decrypt" => return Ok(CCLang::Decrypt),
                 "sign" => return Ok(CCLang::Sign),
                 "verify" => return Ok(CCLang::Verify),
+                "unwrapkey" => return Ok(CCLang::UnwrapKey),
+                "multikey" => return Ok(CCLang::MultiKey),
+                "secret" => return Ok(CCLang::ToSecret),
+                "reveal" => return Ok(CCLang::Reveal),
                 "hash" => return Ok(CCLang::Hash),
+                "merkleroot" => return Ok(CCLang::MerkleRoot),
+                "merkleverify" => return Ok(CCLang::MerkleVerify),
+                "tomultihash" => return Ok(CCLang::ToMultihash),
+                "frommultihash" => return Ok(CCLang::FromMultihash),
+                "multihashverify" => return Ok(CCLang::MultihashVerify),
+                "callhash" => return Ok(CCLang::CallHash),
+                "split" => return Ok(CCLang::Split),
+                "combine" => return Ok(CCLang::Combine),
                 "concat" => return Ok(CCLang::Concat),
                 "slice" => return Ok(CCLang::Slice),
+                "pack" => return Ok(CCLang::Pack),
+                "unpack" => return Ok(CCLang::Unpack),
+                "contains" => return Ok(CCLang::Contains),
+                "intersectcount" => return Ok(CCLang::IntersectCount),
+                "unique" => return Ok(CCLang::Unique),
                 "dup" => return Ok(CCLang::Dup),
                 "pop" => return Ok(CCLang::Pop),
+                "swap" => return Ok(CCLang::Swap),
+                "over" => return Ok(CCLang::Over),
+                "rot" => return Ok(CCLang::Rot),
+                "nip" => return Ok(CCLang::Nip),
+                "tuck" => return Ok(CCLang::Tuck),
+                "pick" => return Ok(CCLang::Pick),
+                "roll" => return Ok(CCLang::Roll),
+                "depth" => return Ok(CCLang::Depth),
+                "dropn" => return Ok(CCLang::DropN),
+                "len" => return Ok(CCLang::Len),
+                "typeof" => return Ok(CCLang::TypeOf),
+                "isbinary" => return Ok(CCLang::IsBinary),
+                "istext" => return Ok(CCLang::IsText),
+                "isboolean" => return Ok(CCLang::IsBoolean),
+                "isindex" => return Ok(CCLang::IsIndex),
+                "issecret" => return Ok(CCLang::IsSecret),
+                "getvar" => return Ok(CCLang::GetVar),
+                "now" => return Ok(CCLang::Now),
+                "checktime" => return Ok(CCLang::CheckTime),
+                "assert" => return Ok(CCLang::Assert),
                 "if" => return Ok(CCLang::If),
                 "else" => return Ok(CCLang::Else),
                 "fi" => return Ok(CCLang::Fi),
+                "repeat" => return Ok(CCLang::Repeat),
+                "loop" => return Ok(CCLang::Loop),
+                "return" => return Ok(CCLang::Return),
+                "halt" => return Ok(CCLang::Halt),
+                "define" => return Ok(CCLang::Define),
+                "enddef" => return Ok(CCLang::EndDefine),
+                "invoke" => return Ok(CCLang::Invoke),
                 &_ => {
                     match v.parse::<isize>() {
                         Ok(i) => return Ok(CCLang::Index(i)),
+                        _ if v == REDACTED => {
+                            return Err(E::custom("a Secret has no text form"));
+                        },
                         _ => {
                             return Ok(CCLang::Text(v.to_string()));
                         }
@@ -393,6 +1257,41 @@ impl<'de> de::Visitor<'de> for CCLangVisitor {
     }
 }
 
+impl CCLang {
+    /// Check that any algorithm the item names is built in
+    pub fn supported(&self) -> Result<(), UnsupportedAlgorithm> {
+        match self {
+            CCLang::EncodingId(e) => e.supported(),
+            CCLang::EncryptionId(e) => e.supported(),
+            CCLang::SigningId(s) => s.supported(),
+            CCLang::HashingId(h) => h.supported(),
+            CCLang::List(l) => l.iter().try_for_each(CCLang::supported),
+            _ => Ok(())
+        }
+    }
+
+    /// The name of the type of a data value, as pushed by 'TYPEOF'
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            CCLang::Boolean(_) => "Boolean",
+            CCLang::Binary(_) => "Binary",
+            CCLang::Text(_) => "Text",
+            CCLang::EncodingId(_) => "EncodingId",
+            CCLang::EncryptionId(_) => "EncryptionId",
+            CCLang::SigningId(_) => "SigningId",
+            CCLang::HashingId(_) => "HashingId",
+            CCLang::List(_) => "List",
+            CCLang::Secret(_) => "Secret",
+            CCLang::Error(_) => "Error",
+            CCLang::Index(_) => "Index",
+            CCLang::Handle(_) => "Handle",
+            CCLang::Whence(_) => "Whence",
+            CCLang::Mode(_) => "Mode",
+            _ => "Opcode"
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for CCLang {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<CCLang, D::Error> {
         d.deserialize_any(CCLangVisitor)
@@ -406,11 +1305,17 @@ impl fmt::Debug for CCLang {
             CCLang::Boolean(v) => write!(f, "Boolean({})", if *v { "TRUE" } else { "FALSE" }),
             CCLang::Binary(v) => write!(f, "Binary({})", hex::encode(v.as_ref())),
             CCLang::Text(v) => write!(f, "Text({})", v),
+            CCLang::Secret(v) => write!(f, "{:?}", v),
             CCLang::EncodingId(encoding) => write!(f, "EncodingId({})", encoding),
             CCLang::EncryptionId(encryption) => write!(f, "EncryptionId({})", encryption),
             CCLang::SigningId(signing) => write!(f, "SigningId({})", signing),
             CCLang::HashingId(hashing) => write!(f, "HashingId({})", hashing),
+            CCLang::List(l) => {
+                let items: Vec<String> = l.iter().map(|i| format!("{:?}", i)).collect();
+                write!(f, "List({})", items.join(", "))
+            },
             CCLang::Index(v) => write!(f, "Index({})", v),
+            CCLang::Error(e) => write!(f, "Error({})", e),
             CCLang::Handle(_) => write!(f, "Handle"),
             CCLang::Whence(w) => write!(f, "Whence({})", w),
             CCLang::Mode(m) => write!(f, "Mode({})", m),
@@ -425,6 +1330,17 @@ impl fmt::Debug for CCLang {
             CCLang::LessThanEqual => write!(f, "<="),
             CCLang::GreaterThan => write!(f, ">"),
             CCLang::GreaterThanEqual => write!(f, ">="),
+            CCLang::Add => write!(f, "ADD"),
+            CCLang::Sub => write!(f, "SUB"),
+            CCLang::Mul => write!(f, "MUL"),
+            CCLang::Div => write!(f, "DIV"),
+            CCLang::Mod => write!(f, "MOD"),
+            CCLang::Min => write!(f, "MIN"),
+            CCLang::Max => write!(f, "MAX"),
+            CCLang::Abs => write!(f, "ABS"),
+            CCLang::And => write!(f, "AND"),
+            CCLang::Or => write!(f, "OR"),
+            CCLang::Not => write!(f, "NOT"),
             /*
             CCLang::BitOr => write!(f, "|"),
             CCLang::BitAnd => write!(f, "&"),
@@ -433,18 +1349,64 @@ impl fmt::Debug for CCLang {
             */
             CCLang::Encode => write!(f, "ENCODE"),
             CCLang::Decode => write!(f, "DECODE"),
+            CCLang::ToBytes => write!(f, "TOBYTES"),
+            CCLang::ToText => write!(f, "TOTEXT"),
             CCLang::Encrypt => write!(f, "ENCRYPT"),
             CCLang::Decrypt => write!(f, "DECRYPT"),
             CCLang::Sign => write!(f, "SIGN"),
             CCLang::Verify => write!(f, "VERIFY"),
+            CCLang::UnwrapKey => write!(f, "UNWRAPKEY"),
+            CCLang::MultiKey => write!(f, "MULTIKEY"),
+            CCLang::ToSecret => write!(f, "SECRET"),
+            CCLang::Reveal => write!(f, "REVEAL"),
             CCLang::Hash => write!(f, "HASH"),
+            CCLang::MerkleRoot => write!(f, "MERKLEROOT"),
+            CCLang::MerkleVerify => write!(f, "MERKLEVERIFY"),
+            CCLang::ToMultihash => write!(f, "TOMULTIHASH"),
+            CCLang::FromMultihash => write!(f, "FROMMULTIHASH"),
+            CCLang::MultihashVerify => write!(f, "MULTIHASHVERIFY"),
+            CCLang::CallHash => write!(f, "CALLHASH"),
+            CCLang::Split => write!(f, "SPLIT"),
+            CCLang::Combine => write!(f, "COMBINE"),
             CCLang::Concat => write!(f, "CONCAT"),
             CCLang::Slice => write!(f, "SLICE"),
+            CCLang::Pack => write!(f, "PACK"),
+            CCLang::Unpack => write!(f, "UNPACK"),
+            CCLang::Contains => write!(f, "CONTAINS"),
+            CCLang::IntersectCount => write!(f, "INTERSECTCOUNT"),
+            CCLang::Unique => write!(f, "UNIQUE"),
             CCLang::Dup => write!(f, "DUP"),
             CCLang::Pop => write!(f, "POP"),
+            CCLang::Swap => write!(f, "SWAP"),
+            CCLang::Over => write!(f, "OVER"),
+            CCLang::Rot => write!(f, "ROT"),
+            CCLang::Nip => write!(f, "NIP"),
+            CCLang::Tuck => write!(f, "TUCK"),
+            CCLang::Pick => write!(f, "PICK"),
+            CCLang::Roll => write!(f, "ROLL"),
+            CCLang::Depth => write!(f, "DEPTH"),
+            CCLang::DropN => write!(f, "DROPN"),
+            CCLang::Len => write!(f, "LEN"),
+            CCLang::TypeOf => write!(f, "TYPEOF"),
+            CCLang::IsBinary => write!(f, "ISBINARY"),
+            CCLang::IsText => write!(f, "ISTEXT"),
+            CCLang::IsBoolean => write!(f, "ISBOOLEAN"),
+            CCLang::IsIndex => write!(f, "ISINDEX"),
+            CCLang::IsSecret => write!(f, "ISSECRET"),
+            CCLang::GetVar => write!(f, "GETVAR"),
+            CCLang::Now => write!(f, "NOW"),
+            CCLang::CheckTime => write!(f, "CHECKTIME"),
+            CCLang::Assert => write!(f, "ASSERT"),
             CCLang::If => write!(f, "IF"),
             CCLang::Else => write!(f, "ELSE"),
-            CCLang::Fi => write!(f, "FI")
+            CCLang::Fi => write!(f, "FI"),
+            CCLang::Repeat => write!(f, "REPEAT"),
+            CCLang::Loop => write!(f, "LOOP"),
+            CCLang::Return => write!(f, "RETURN"),
+            CCLang::Halt => write!(f, "HALT"),
+            CCLang::Define => write!(f, "DEFINE"),
+            CCLang::EndDefine => write!(f, "ENDDEF"),
+            CCLang::Invoke => write!(f, "INVOKE")
         }
     }
 }
@@ -456,11 +1418,23 @@ impl fmt::Display for CCLang {
             CCLang::Boolean(v) => write!(f, "{}", if *v { "TRUE" } else { "FALSE" }),
             CCLang::Binary(v) => write!(f, "{} {} {}", hex::encode(v.as_ref()), CCLang::EncodingId(Encoding::Hex), CCLang::Decode),
             CCLang::Text(v) => write!(f, "{}", v),
+            // the bytes are never shown, and the text doesn't parse back so a
+            // serialized script can't quietly lose the Secret
+            CCLang::Secret(_) => write!(f, "{}", REDACTED),
             CCLang::EncodingId(encoding) => write!(f, "{}", encoding),
             CCLang::EncryptionId(encryption) => write!(f, "{}", encryption),
             CCLang::SigningId(signing) => write!(f, "{}", signing),
             CCLang::HashingId(hashing) => write!(f, "{}", hashing),
+            CCLang::List(l) => {
+                for i in l {
+                    write!(f, "{} ", i)?;
+                }
+                write!(f, "{} {}", l.len(), CCLang::Pack)
+            },
             CCLang::Index(v) => write!(f, "{}", v),
+            // shown so a failed machine's stack can be printed, it doesn't parse
+            // back into an Error
+            CCLang::Error(e) => write!(f, "ERROR({})", e),
             CCLang::Handle(_) => panic!("cannot serialize Handle"),
             CCLang::Whence(w) => write!(f, "{}", w),
             CCLang::Mode(m) => write!(f, "{}", m),
@@ -475,6 +1449,17 @@ impl fmt::Display for CCLang {
             CCLang::LessThanEqual => write!(f, "<="),
             CCLang::GreaterThan => write!(f, ">"),
             CCLang::GreaterThanEqual => write!(f, ">="),
+            CCLang::Add => write!(f, "ADD"),
+            CCLang::Sub => write!(f, "SUB"),
+            CCLang::Mul => write!(f, "MUL"),
+            CCLang::Div => write!(f, "DIV"),
+            CCLang::Mod => write!(f, "MOD"),
+            CCLang::Min => write!(f, "MIN"),
+            CCLang::Max => write!(f, "MAX"),
+            CCLang::Abs => write!(f, "ABS"),
+            CCLang::And => write!(f, "AND"),
+            CCLang::Or => write!(f, "OR"),
+            CCLang::Not => write!(f, "NOT"),
             /*
             CCLang::BitOr => write!(f, "|"),
             CCLang::BitAnd => write!(f, "&"),
@@ -483,18 +1468,64 @@ impl fmt::Display for CCLang {
             */
             CCLang::Encode => write!(f, "ENCODE"),
             CCLang::Decode => write!(f, "DECODE"),
+            CCLang::ToBytes => write!(f, "TOBYTES"),
+            CCLang::ToText => write!(f, "TOTEXT"),
             CCLang::Encrypt => write!(f, "ENCRYPT"),
             CCLang::Decrypt => write!(f, "DECRYPT"),
             CCLang::Sign => write!(f, "SIGN"),
             CCLang::Verify => write!(f, "VERIFY"),
+            CCLang::UnwrapKey => write!(f, "UNWRAPKEY"),
+            CCLang::MultiKey => write!(f, "MULTIKEY"),
+            CCLang::ToSecret => write!(f, "SECRET"),
+            CCLang::Reveal => write!(f, "REVEAL"),
             CCLang::Hash => write!(f, "HASH"),
+            CCLang::MerkleRoot => write!(f, "MERKLEROOT"),
+            CCLang::MerkleVerify => write!(f, "MERKLEVERIFY"),
+            CCLang::ToMultihash => write!(f, "TOMULTIHASH"),
+            CCLang::FromMultihash => write!(f, "FROMMULTIHASH"),
+            CCLang::MultihashVerify => write!(f, "MULTIHASHVERIFY"),
+            CCLang::CallHash => write!(f, "CALLHASH"),
+            CCLang::Split => write!(f, "SPLIT"),
+            CCLang::Combine => write!(f, "COMBINE"),
             CCLang::Concat => write!(f, "CONCAT"),
             CCLang::Slice => write!(f, "SLICE"),
+            CCLang::Pack => write!(f, "PACK"),
+            CCLang::Unpack => write!(f, "UNPACK"),
+            CCLang::Contains => write!(f, "CONTAINS"),
+            CCLang::IntersectCount => write!(f, "INTERSECTCOUNT"),
+            CCLang::Unique => write!(f, "UNIQUE"),
             CCLang::Dup => write!(f, "DUP"),
             CCLang::Pop => write!(f, "POP"),
+            CCLang::Swap => write!(f, "SWAP"),
+            CCLang::Over => write!(f, "OVER"),
+            CCLang::Rot => write!(f, "ROT"),
+            CCLang::Nip => write!(f, "NIP"),
+            CCLang::Tuck => write!(f, "TUCK"),
+            CCLang::Pick => write!(f, "PICK"),
+            CCLang::Roll => write!(f, "ROLL"),
+            CCLang::Depth => write!(f, "DEPTH"),
+            CCLang::DropN => write!(f, "DROPN"),
+            CCLang::Len => write!(f, "LEN"),
+            CCLang::TypeOf => write!(f, "TYPEOF"),
+            CCLang::IsBinary => write!(f, "ISBINARY"),
+            CCLang::IsText => write!(f, "ISTEXT"),
+            CCLang::IsBoolean => write!(f, "ISBOOLEAN"),
+            CCLang::IsIndex => write!(f, "ISINDEX"),
+            CCLang::IsSecret => write!(f, "ISSECRET"),
+            CCLang::GetVar => write!(f, "GETVAR"),
+            CCLang::Now => write!(f, "NOW"),
+            CCLang::CheckTime => write!(f, "CHECKTIME"),
+            CCLang::Assert => write!(f, "ASSERT"),
             CCLang::If => write!(f, "IF"),
             CCLang::Else => write!(f, "ELSE"),
-            CCLang::Fi => write!(f, "FI")
+            CCLang::Fi => write!(f, "FI"),
+            CCLang::Repeat => write!(f, "REPEAT"),
+            CCLang::Loop => write!(f, "LOOP"),
+            CCLang::Return => write!(f, "RETURN"),
+            CCLang::Halt => write!(f, "HALT"),
+            CCLang::Define => write!(f, "DEFINE"),
+            CCLang::EndDefine => write!(f, "ENDDEF"),
+            CCLang::Invoke => write!(f, "INVOKE")
         }
     }
 }
@@ -515,7 +1546,9 @@ impl PartialEq for CCLang {
             },
             CCLang::Binary(l) => {
                 match other {
-                    CCLang::Binary(r) => *l == *r,
+                    // constant-time so comparing MACs, hashes or decrypted
+                    // tokens doesn't leak how many leading bytes matched
+                    CCLang::Binary(r) => memcmp(l.as_ref(), r.as_ref()),
                     _ => false
                 }
             },
@@ -525,9 +1558,15 @@ impl PartialEq for CCLang {
                     _ => false
                 }
             },
-            CCLang::EncodingId(l) => {
+            CCLang::Secret(l) => {
                 match other {
-                    CCLang::EncodingId(r) => *l == *r,
+                    CCLang::Secret(r) => *l == *r,
+                    _ => false
+                }
+            },
+            CCLang::EncodingId(l) => {
+                match other {
+                    CCLang::EncodingId(r) => *l == *r,
                     _ => false
                 }
             },
@@ -549,6 +1588,18 @@ impl PartialEq for CCLang {
                     _ => false
                 }
             },
+            CCLang::List(l) => {
+                match other {
+                    CCLang::List(r) => *l == *r,
+                    _ => false
+                }
+            },
+            CCLang::Error(l) => {
+                match other {
+                    CCLang::Error(r) => *l == *r,
+                    _ => false
+                }
+            },
             CCLang::Handle(_) => {
                 true
             },
@@ -587,6 +1638,17 @@ impl PartialEq for CCLang {
             CCLang::LessThanEqual => { match other { CCLang::LessThanEqual => true, _ => false } },
             CCLang::GreaterThan => { match other { CCLang::GreaterThan => true, _ => false } },
             CCLang::GreaterThanEqual => { match other { CCLang::GreaterThanEqual => true, _ => false } },
+            CCLang::Add => { match other { CCLang::Add => true, _ => false } },
+            CCLang::Sub => { match other { CCLang::Sub => true, _ => false } },
+            CCLang::Mul => { match other { CCLang::Mul => true, _ => false } },
+            CCLang::Div => { match other { CCLang::Div => true, _ => false } },
+            CCLang::Mod => { match other { CCLang::Mod => true, _ => false } },
+            CCLang::Min => { match other { CCLang::Min => true, _ => false } },
+            CCLang::Max => { match other { CCLang::Max => true, _ => false } },
+            CCLang::Abs => { match other { CCLang::Abs => true, _ => false } },
+            CCLang::And => { match other { CCLang::And => true, _ => false } },
+            CCLang::Or => { match other { CCLang::Or => true, _ => false } },
+            CCLang::Not => { match other { CCLang::Not => true, _ => false } },
             /*
             CCLang::BitOr => { match other { CCLang::BitOr => true, _ => false } },
             CCLang::BitAnd => { match other { CCLang::BitAnd => true, _ => false } },
@@ -595,18 +1657,64 @@ impl PartialEq for CCLang {
             */
             CCLang::Encode => { match other { CCLang::Encode => true, _ => false } },
             CCLang::Decode => { match other { CCLang::Decode => true, _ => false } },
+            CCLang::ToBytes => { match other { CCLang::ToBytes => true, _ => false } },
+            CCLang::ToText => { match other { CCLang::ToText => true, _ => false } },
             CCLang::Encrypt => { match other { CCLang::Encrypt => true, _ => false } },
             CCLang::Decrypt => { match other { CCLang::Decrypt => true, _ => false } },
             CCLang::Sign => { match other { CCLang::Sign => true, _ => false } },
             CCLang::Verify => { match other { CCLang::Verify => true, _ => false } },
+            CCLang::UnwrapKey => { match other { CCLang::UnwrapKey => true, _ => false } },
+            CCLang::MultiKey => { match other { CCLang::MultiKey => true, _ => false } },
+            CCLang::ToSecret => { match other { CCLang::ToSecret => true, _ => false } },
+            CCLang::Reveal => { match other { CCLang::Reveal => true, _ => false } },
             CCLang::Hash => { match other { CCLang::Hash => true, _ => false } },
+            CCLang::MerkleRoot => { match other { CCLang::MerkleRoot => true, _ => false } },
+            CCLang::MerkleVerify => { match other { CCLang::MerkleVerify => true, _ => false } },
+            CCLang::ToMultihash => { match other { CCLang::ToMultihash => true, _ => false } },
+            CCLang::FromMultihash => { match other { CCLang::FromMultihash => true, _ => false } },
+            CCLang::MultihashVerify => { match other { CCLang::MultihashVerify => true, _ => false } },
+            CCLang::CallHash => { match other { CCLang::CallHash => true, _ => false } },
+            CCLang::Split => { match other { CCLang::Split => true, _ => false } },
+            CCLang::Combine => { match other { CCLang::Combine => true, _ => false } },
             CCLang::Concat => { match other { CCLang::Concat => true, _ => false } },
             CCLang::Slice => { match other { CCLang::Slice => true, _ => false } },
+            CCLang::Pack => { match other { CCLang::Pack => true, _ => false } },
+            CCLang::Unpack => { match other { CCLang::Unpack => true, _ => false } },
+            CCLang::Contains => { match other { CCLang::Contains => true, _ => false } },
+            CCLang::IntersectCount => { match other { CCLang::IntersectCount => true, _ => false } },
+            CCLang::Unique => { match other { CCLang::Unique => true, _ => false } },
             CCLang::Dup => { match other { CCLang::Dup => true, _ => false } },
             CCLang::Pop => { match other { CCLang::Pop => true, _ => false } },
+            CCLang::Swap => { match other { CCLang::Swap => true, _ => false } },
+            CCLang::Over => { match other { CCLang::Over => true, _ => false } },
+            CCLang::Rot => { match other { CCLang::Rot => true, _ => false } },
+            CCLang::Nip => { match other { CCLang::Nip => true, _ => false } },
+            CCLang::Tuck => { match other { CCLang::Tuck => true, _ => false } },
+            CCLang::Pick => { match other { CCLang::Pick => true, _ => false } },
+            CCLang::Roll => { match other { CCLang::Roll => true, _ => false } },
+            CCLang::Depth => { match other { CCLang::Depth => true, _ => false } },
+            CCLang::DropN => { match other { CCLang::DropN => true, _ => false } },
+            CCLang::Len => { match other { CCLang::Len => true, _ => false } },
+            CCLang::TypeOf => { match other { CCLang::TypeOf => true, _ => false } },
+            CCLang::IsBinary => { match other { CCLang::IsBinary => true, _ => false } },
+            CCLang::IsText => { match other { CCLang::IsText => true, _ => false } },
+            CCLang::IsBoolean => { match other { CCLang::IsBoolean => true, _ => false } },
+            CCLang::IsIndex => { match other { CCLang::IsIndex => true, _ => false } },
+            CCLang::IsSecret => { match other { CCLang::IsSecret => true, _ => false } },
+            CCLang::GetVar => { match other { CCLang::GetVar => true, _ => false } },
+            CCLang::Now => { match other { CCLang::Now => true, _ => false } },
+            CCLang::CheckTime => { match other { CCLang::CheckTime => true, _ => false } },
+            CCLang::Assert => { match other { CCLang::Assert => true, _ => false } },
             CCLang::If => { match other { CCLang::If => true, _ => false } },
             CCLang::Else => { match other { CCLang::Else => true, _ => false } },
-            CCLang::Fi => { match other { CCLang::Fi => true, _ => false } }
+            CCLang::Fi => { match other { CCLang::Fi => true, _ => false } },
+            CCLang::Repeat => { match other { CCLang::Repeat => true, _ => false } },
+            CCLang::Loop => { match other { CCLang::Loop => true, _ => false } },
+            CCLang::Return => { match other { CCLang::Return => true, _ => false } },
+            CCLang::Halt => { match other { CCLang::Halt => true, _ => false } },
+            CCLang::Define => { match other { CCLang::Define => true, _ => false } },
+            CCLang::EndDefine => { match other { CCLang::EndDefine => true, _ => false } },
+            CCLang::Invoke => { match other { CCLang::Invoke => true, _ => false } }
         }
     }
 }
@@ -636,6 +1744,10 @@ impl PartialOrd for CCLang {
                 }
                 None
             },
+            CCLang::Secret(_) => {
+                // secrets are only ever compared for equality
+                None
+            },
             CCLang::EncodingId(l) => {
                 if let CCLang::EncodingId(r) = other {
                     return l.partial_cmp(r);
@@ -660,6 +1772,13 @@ impl PartialOrd for CCLang {
                 }
                 None
             },
+            CCLang::List(_) => {
+                // lists are only ever compared for equality
+                None
+            },
+            CCLang::Error(_) => {
+                None
+            },
             CCLang::Handle(_) => {
                 Some(Ordering::Equal)
             },
@@ -690,10 +1809,22 @@ impl PartialOrd for CCLang {
     }
 }
 
+// a machine run on its own runs with an empty context
 impl Instruction<CCLang> for CCLang {
     fn execute(&self, ip: usize, m: &mut Machine<CCLang>, io: &dyn AppIO<CCLang>) {
+        self.step(ip, m, io, &Context::new());
+    }
+}
+
+impl CCLang {
+    // execute the instruction with the values in the context
+    pub(crate) fn step(&self, ip: usize, m: &mut Machine<CCLang>, io: &dyn AppIO<CCLang>, ctx: &Context) {
+        if !permitted(m, ctx, self) {
+            return;
+        }
         match self {
             CCLang::Handle(_) => panic!(),
+            CCLang::Error(e) => fail(m, e),
             CCLang::Version => {
                 if let Some(CCLang::Text(s)) = m.pop() {
                     if let Ok(v) = Version::parse(&s) {
@@ -711,9 +1842,15 @@ impl Instruction<CCLang> for CCLang {
             CCLang::EncryptionId(_) |
             CCLang::SigningId(_) |
             CCLang::HashingId(_) |
+            CCLang::List(_) |
             CCLang::Index(_) |
             CCLang::Whence(_) |
             CCLang::Mode(_) => {
+                // algorithms that aren't built in can't be used
+                if let Err(e) = self.supported() {
+                    fail(m, &e.to_string());
+                    return;
+                }
                 // just push the immediate constant onto the stack
                 m.push(self.clone());
                 m.pushr(ip + 1);
@@ -778,6 +1915,68 @@ impl Instruction<CCLang> for CCLang {
                 }
                 panic!();
             },
+            CCLang::Add => arithmetic(ip, m, |l, r| l.checked_add(r).ok_or("integer overflow")),
+            CCLang::Sub => arithmetic(ip, m, |l, r| l.checked_sub(r).ok_or("integer overflow")),
+            CCLang::Mul => arithmetic(ip, m, |l, r| l.checked_mul(r).ok_or("integer overflow")),
+            CCLang::Div => {
+                arithmetic(ip, m, |l, r| {
+                    if r == 0 {
+                        return Err("division by zero");
+                    }
+                    l.checked_div(r).ok_or("integer overflow")
+                })
+            },
+            CCLang::Mod => {
+                arithmetic(ip, m, |l, r| {
+                    if r == 0 {
+                        return Err("division by zero");
+                    }
+                    l.checked_rem(r).ok_or("integer overflow")
+                })
+            },
+            CCLang::Min => arithmetic(ip, m, |l, r| Ok(l.min(r))),
+            CCLang::Max => arithmetic(ip, m, |l, r| Ok(l.max(r))),
+            CCLang::And => {
+                if let Some(CCLang::Boolean(r)) = m.pop() {
+                    if let Some(CCLang::Boolean(l)) = m.pop() {
+                        m.push(CCLang::Boolean(l && r));
+                        m.pushr(ip + 1);
+                        return;
+                    }
+                }
+                panic!()
+            },
+            CCLang::Or => {
+                if let Some(CCLang::Boolean(r)) = m.pop() {
+                    if let Some(CCLang::Boolean(l)) = m.pop() {
+                        m.push(CCLang::Boolean(l || r));
+                        m.pushr(ip + 1);
+                        return;
+                    }
+                }
+                panic!()
+            },
+            CCLang::Not => {
+                if let Some(CCLang::Boolean(b)) = m.pop() {
+                    m.push(CCLang::Boolean(!b));
+                    m.pushr(ip + 1);
+                    return;
+                }
+                panic!()
+            },
+            CCLang::Abs => {
+                if let Some(i) = m.pop().and_then(to_index) {
+                    match i.checked_abs() {
+                        Some(a) => {
+                            m.push(CCLang::Index(a));
+                            m.pushr(ip + 1);
+                        },
+                        None => fail(m, "integer overflow")
+                    }
+                    return;
+                }
+                panic!()
+            },
             /*
             CCLang::BitOr =>
             CCLang::BitAnd =>
@@ -786,53 +1985,132 @@ impl Instruction<CCLang> for CCLang {
             */
             CCLang::Decode => {
                 if let Some(CCLang::EncodingId(id)) = m.pop() {
-                    if let Some(CCLang::Text(s)) = m.pop() {
-                        let mut b = BytesMut::new();
-                        let data = match id {
-                            Encoding::Hex => hex::decode(s).unwrap(),
-                            Encoding::Base64 => base64::decode_config(&s, base64::STANDARD).unwrap(),
-                            Encoding::Base64Url => base64::decode_config(&s, base64::URL_SAFE).unwrap(),
-                            Encoding::Base58Bitcoin => bs58::decode(s).into_vec().unwrap()
-                        };
-                        b.put_slice(&data);
-                        m.push(CCLang::Binary(b.freeze()));
-                        m.pushr(ip + 1);
+                    if !usable(m, ctx, CCLang::EncodingId(id)) {
                         return;
                     }
+                    let v = m.pop();
+                    let (text, secret) = match &v {
+                        Some(CCLang::Text(t)) => (t.as_str(), false),
+                        Some(CCLang::Secret(s)) => {
+                            // encoded key material decodes straight into a
+                            // Secret without passing through a Binary
+                            match std::str::from_utf8(s.as_ref()) {
+                                Ok(t) => (t, true),
+                                Err(_) => {
+                                    fail(m, "secret is not encoded text");
+                                    return;
+                                }
+                            }
+                        },
+                        _ => panic!()
+                    };
+
+                    // the policy has to allow the encoding a multibase text
+                    // uses as well
+                    if id == Encoding::Multibase {
+                        if let Some(e) = Encoding::multibase_encoding(text) {
+                            if !permitted(m, ctx, &CCLang::EncodingId(e)) {
+                                return;
+                            }
+                        }
+                    }
+
+                    // labeled encodings push the label and then the data
+                    let decoded = if id.is_labeled() {
+                        id.decode_labeled(text).map(|(l, d)| (Some(l), d))
+                    } else {
+                        id.decode(text).map(|d| (None, d))
+                    };
+                    match decoded {
+                        Ok((label, data)) => {
+                            if let Some(l) = label {
+                                m.push(CCLang::Text(l));
+                            }
+                            if secret {
+                                m.push(CCLang::Secret(Secret::from(data)));
+                            } else {
+                                m.push(CCLang::Binary(Bytes::from(data)));
+                            }
+                            m.pushr(ip + 1);
+                        },
+                        Err(e) => fail(m, &e)
+                    }
+                    return;
                 }
                 panic!()
             },
             CCLang::Encode => {
                 if let Some(CCLang::EncodingId(id)) = m.pop() {
-                    if let Some(CCLang::Binary(b)) = m.pop() {
-                        let s = match id {
-                            Encoding::Hex => hex::encode(b.as_ref()),
-                            Encoding::Base64 => base64::encode_config(b.as_ref(), base64::STANDARD),
-                            Encoding::Base64Url => base64::encode_config(b.as_ref(), base64::URL_SAFE),
-                            Encoding::Base58Bitcoin => bs58::encode(b.as_ref()).into_string(),
-                        };
-                        m.push(CCLang::Text(s));
-                        m.pushr(ip + 1);
+                    if !usable(m, ctx, CCLang::EncodingId(id)) {
+                        return;
+                    }
+                    // multibase always encodes with base58btc
+                    if id == Encoding::Multibase && !permitted(m, ctx, &CCLang::EncodingId(Encoding::Base58Bitcoin)) {
                         return;
                     }
+                    let encoded = match m.pop() {
+                        Some(CCLang::Binary(b)) => {
+                            // labeled encodings pop the label from under the data
+                            if id.is_labeled() {
+                                match m.pop() {
+                                    Some(CCLang::Text(l)) => id.encode_labeled(&l, b.as_ref()),
+                                    _ => panic!()
+                                }
+                            } else {
+                                id.encode(b.as_ref())
+                            }
+                        },
+                        Some(CCLang::Secret(_)) => Err("cannot encode a Secret without REVEAL".to_string()),
+                        _ => panic!()
+                    };
+                    match encoded {
+                        Ok(t) => {
+                            m.push(CCLang::Text(t));
+                            m.pushr(ip + 1);
+                        },
+                        Err(e) => fail(m, &e)
+                    }
+                    return;
+                }
+                panic!()
+            },
+            CCLang::ToBytes => {
+                if let Some(CCLang::Text(t)) = m.pop() {
+                    m.push(CCLang::Binary(Bytes::from(t.into_bytes())));
+                    m.pushr(ip + 1);
+                    return;
+                }
+                panic!()
+            },
+            CCLang::ToText => {
+                if let Some(CCLang::Binary(b)) = m.pop() {
+                    match String::from_utf8(b.to_vec()) {
+                        Ok(t) => {
+                            m.push(CCLang::Text(t));
+                            m.pushr(ip + 1);
+                        },
+                        Err(e) => fail(m, &format!("binary is not valid UTF-8: {}", e.utf8_error()))
+                    }
+                    return;
                 }
                 panic!()
             },
             CCLang::Encrypt => {
                 if let Some(CCLang::EncryptionId(id)) = m.pop() {
+                    if !usable(m, ctx, CCLang::EncryptionId(id)) {
+                        return;
+                    }
                     match id {
                         Encryption::XSalsa20Poly1305 => {
                             if let Some(CCLang::Binary(n)) = m.pop() {
-                                if let Some(CCLang::Binary(k)) = m.pop() {
+                                if let Some(k) = m.pop().and_then(Key::from) {
                                     if let Some(CCLang::Binary(plaintext)) = m.pop() {
-                                        let nonce = xsalsa20poly1305::Nonce::from_slice(n.as_ref()).unwrap();
-                                        let key = xsalsa20poly1305::Key::from_slice(k.as_ref()).unwrap();
-                                        let mut b = BytesMut::new();
-                                        let ciphertext = xsalsa20poly1305::seal(plaintext.as_ref(), &nonce, &key);
-                                        b.put_slice(&ciphertext);
-                                        m.push(CCLang::Binary(b.freeze()));
-                                        m.pushr(ip + 1);
-                                        return;
+                                        let ciphertext = ctx.crypto().seal(id, k.as_ref(), n.as_ref(), plaintext.as_ref());
+                                        if let Ok(ciphertext) = ciphertext {
+                                            m.push(CCLang::Binary(ciphertext));
+                                            m.pushr(ip + 1);
+                                            return;
+                                        }
                                     }
                                 }
                             }
@@ -843,17 +2121,22 @@ impl Instruction<CCLang> for CCLang {
             },
             CCLang::Decrypt => {
                 if let Some(CCLang::EncryptionId(id)) = m.pop() {
+                    if !usable(m, ctx, CCLang::EncryptionId(id)) {
+                        return;
+                    }
                     match id {
                         Encryption::XSalsa20Poly1305 => {
                             if let Some(CCLang::Binary(n)) = m.pop() {
-                                if let Some(CCLang::Binary(k)) = m.pop() {
+                                if let Some(k) = m.pop().and_then(Key::from) {
                                     if let Some(CCLang::Binary(ciphertext)) = m.pop() {
-                                        let nonce = xsalsa20poly1305::Nonce::from_slice(n.as_ref()).unwrap();
-                                        let key = xsalsa20poly1305::Key::from_slice(k.as_ref()).unwrap();
-                                        let mut b = BytesMut::new();
-                                        if let Ok(plaintext) = xsalsa20poly1305::open(ciphertext.as_ref(), &nonce, &key) {
-                                            b.put_slice(&plaintext);
-                                            m.push(CCLang::Binary(b.freeze()));
+                                        let plaintext = ctx.crypto().open(id, k.as_ref(), n.as_ref(), ciphertext.as_ref());
+                                        if let Ok(plaintext) = plaintext {
+                                            // anything unwrapped with a secret key is
+                                            // treated as key material as well
+                                            match k {
+                                                Key::Secret(_) => m.push(CCLang::Secret(Secret::from(plaintext))),
+                                                Key::Binary(_) => m.push(CCLang::Binary(Bytes::from(plaintext)))
+                                            }
                                             m.pushr(ip + 1);
                                             return;
                                         }
@@ -867,15 +2150,33 @@ impl Instruction<CCLang> for CCLang {
             },
             CCLang::Sign => {
                 if let Some(CCLang::SigningId(id)) = m.pop() {
-                    match id {
-                        Signing::Ed25519 => {
-                            if let Some(CCLang::Binary(sk)) = m.pop() {
-                                if let Some(CCLang::Binary(msg)) = m.pop() {
-                                    let seckey = SecretKey::from_slice(sk.as_ref()).unwrap();
-                                    let sig = sign_detached(&msg, &seckey);
-                                    let mut signature = BytesMut::new();
-                                    signature.put_slice(sig.as_ref());
-                                    m.push(CCLang::Binary(signature.freeze()));
+                    if !usable(m, ctx, CCLang::SigningId(id)) {
+                        return;
+                    }
+                    if let Some(sk) = m.pop().and_then(Key::from) {
+                        if let Some(CCLang::Binary(msg)) = m.pop() {
+                            let signature = ctx.crypto().sign(id, sk.as_ref(), msg.as_ref());
+                            if let Ok(signature) = signature {
+                                m.push(CCLang::Binary(signature));
+                                m.pushr(ip + 1);
+                                return;
+                            }
+                        }
+                    }
+                }
+                panic!()
+            },
+            CCLang::Verify => {
+                if let Some(CCLang::SigningId(id)) = m.pop() {
+                    if !usable(m, ctx, CCLang::SigningId(id)) {
+                        return;
+                    }
+                    if let Some(CCLang::Binary(msg)) = m.pop() {
+                        if let Some(CCLang::Binary(pk)) = m.pop() {
+                            if let Some(CCLang::Binary(sig)) = m.pop() {
+                                let valid = ctx.crypto().verify(id, pk.as_ref(), msg.as_ref(), sig.as_ref());
+                                if let Ok(valid) = valid {
+                                    m.push(CCLang::Boolean(valid));
                                     m.pushr(ip + 1);
                                     return;
                                 }
@@ -885,16 +2186,79 @@ impl Instruction<CCLang> for CCLang {
                 }
                 panic!()
             },
-            CCLang::Verify => {
-                if let Some(CCLang::SigningId(id)) = m.pop() {
-                    match id {
-                        Signing::Ed25519 => {
-                            if let Some(CCLang::Binary(msg)) = m.pop() {
-                                if let Some(CCLang::Binary(pk)) = m.pop() {
-                                    if let Some(CCLang::Binary(sig)) = m.pop() {
-                                        let signature = Signature::from_slice(sig.as_ref()).unwrap();
-                                        let pubkey = PublicKey::from_slice(pk.as_ref()).unwrap();
-                                        m.push(CCLang::Boolean(verify_detached(&signature, &msg, &pubkey)));
+            CCLang::UnwrapKey => {
+                let key = match m.pop() {
+                    Some(CCLang::Binary(b)) => der::unwrap_key_with(ctx.crypto(), b.as_ref()),
+                    Some(CCLang::Secret(s)) => der::unwrap_key_with(ctx.crypto(), s.as_ref()),
+                    _ => panic!()
+                };
+                push_key(ip, m, ctx, key);
+            },
+            CCLang::MultiKey => {
+                let key = match m.pop() {
+                    Some(CCLang::Binary(b)) => multiformats::unwrap_key_with(ctx.crypto(), b.as_ref()),
+                    Some(CCLang::Secret(s)) => multiformats::unwrap_key_with(ctx.crypto(), s.as_ref()),
+                    _ => panic!()
+                };
+                push_key(ip, m, ctx, key);
+            },
+            CCLang::Hash => {
+                if let Some(CCLang::HashingId(id)) = m.pop() {
+                    if !usable(m, ctx, CCLang::HashingId(id)) {
+                        return;
+                    }
+                    if let Some(CCLang::Binary(b)) = m.pop() {
+                        m.push(CCLang::Binary(ctx.crypto().hash(id, b.as_ref())));
+                        m.pushr(ip + 1);
+                        return;
+                    }
+                }
+                panic!()
+            },
+            CCLang::MerkleRoot => {
+                if let Some(CCLang::HashingId(id)) = m.pop() {
+                    if !usable(m, ctx, CCLang::HashingId(id)) {
+                        return;
+                    }
+                    if let Some(CCLang::Index(n)) = m.pop() {
+                        // the leaves are taken first to last
+                        let items = match take(m, n) {
+                            Ok(items) => items,
+                            Err(e) => {
+                                fail(m, e);
+                                return;
+                            }
+                        };
+                        let mut leaves = Vec::new();
+                        for i in items {
+                            match i {
+                                CCLang::Binary(leaf) => leaves.push(leaf),
+                                _ => {
+                                    fail(m, "leaves must be Binary");
+                                    return;
+                                }
+                            }
+                        }
+                        m.push(CCLang::Binary(merkle::root_with(ctx.crypto(), id, &leaves)));
+                        m.pushr(ip + 1);
+                        return;
+                    }
+                }
+                panic!()
+            },
+            CCLang::MerkleVerify => {
+                if let Some(CCLang::HashingId(id)) = m.pop() {
+                    if !usable(m, ctx, CCLang::HashingId(id)) {
+                        return;
+                    }
+                    if let Some(CCLang::Binary(root)) = m.pop() {
+                        if let Some(CCLang::Index(n)) = m.pop() {
+                            if let Some(CCLang::Index(i)) = m.pop() {
+                                if let Some(CCLang::Binary(proof)) = m.pop() {
+                                    if let Some(CCLang::Binary(leaf)) = m.pop() {
+                                        let valid = i >= 0 && n >= 0 &&
+                                            merkle::verify_with(ctx.crypto(), id, leaf.as_ref(), proof.as_ref(), i as usize, n as usize, root.as_ref());
+                                        m.push(CCLang::Boolean(valid));
                                         m.pushr(ip + 1);
                                         return;
                                     }
@@ -905,34 +2269,168 @@ impl Instruction<CCLang> for CCLang {
                 }
                 panic!()
             },
-            CCLang::Hash => {
+            CCLang::ToMultihash => {
                 if let Some(CCLang::HashingId(id)) = m.pop() {
-                    if let Some(CCLang::Binary(b)) = m.pop() {
-                        let mut buf = BytesMut::new();
-                        match id {
-                            Hashing::SHA256 => buf.put_slice(sha256::hash(b.as_ref()).as_ref()),
-                            Hashing::SHA512 => buf.put_slice(sha512::hash(b.as_ref()).as_ref())
+                    if !usable(m, ctx, CCLang::HashingId(id)) {
+                        return;
+                    }
+                    if let Some(CCLang::Binary(d)) = m.pop() {
+                        match multiformats::wrap_multihash(id, d.as_ref()) {
+                            Ok(mh) => {
+                                m.push(CCLang::Binary(mh));
+                                m.pushr(ip + 1);
+                            },
+                            Err(e) => fail(m, e)
                         }
-                        m.push(CCLang::Binary(buf.freeze()));
-                        m.pushr(ip + 1);
                         return;
                     }
                 }
                 panic!()
             },
-            CCLang::Concat => {
-                if let Some(CCLang::Binary(br)) = m.pop() {
-                    if let Some(CCLang::Binary(bl)) = m.pop() {
-                        let mut buf = BytesMut::new();
-                        buf.extend_from_slice(&bl);
-                        buf.extend_from_slice(&br);
-                        m.push(CCLang::Binary(buf.freeze()));
-                        m.pushr(ip + 1);
+            CCLang::FromMultihash => {
+                if let Some(CCLang::Binary(mh)) = m.pop() {
+                    match multiformats::unwrap_multihash(mh.as_ref()) {
+                        Ok((id, d)) => {
+                            if !permitted(m, ctx, &CCLang::HashingId(id)) {
+                                return;
+                            }
+                            m.push(CCLang::Binary(Bytes::copy_from_slice(d)));
+                            m.push(CCLang::HashingId(id));
+                            m.pushr(ip + 1);
+                        },
+                        Err(e) => fail(m, e)
+                    }
+                    return;
+                }
+                panic!()
+            },
+            CCLang::MultihashVerify => {
+                if let Some(CCLang::Binary(mh)) = m.pop() {
+                    if let Some(CCLang::Binary(data)) = m.pop() {
+                        match multiformats::unwrap_multihash(mh.as_ref()) {
+                            Ok((id, d)) => {
+                                if !permitted(m, ctx, &CCLang::HashingId(id)) {
+                                    return;
+                                }
+                                if d.len() < multiformats::MIN_VERIFY_DIGEST {
+                                    fail(m, "multihash digest is too short to verify");
+                                    return;
+                                }
+                                // compare the possibly truncated digest in constant time
+                                let digest = ctx.crypto().hash(id, data.as_ref());
+                                m.push(CCLang::Boolean(memcmp(&digest[..d.len()], d)));
+                                m.pushr(ip + 1);
+                            },
+                            Err(e) => fail(m, e)
+                        }
                         return;
                     }
                 }
                 panic!()
             },
+            CCLang::CallHash => {
+                // ( s h id -- ... )
+                if let Some(CCLang::HashingId(id)) = m.pop() {
+                    if !usable(m, ctx, CCLang::HashingId(id)) {
+                        return;
+                    }
+                    if let Some(CCLang::Binary(h)) = m.pop() {
+                        if let Some(CCLang::Binary(s)) = m.pop() {
+                            if !memcmp(&ctx.crypto().hash(id, s.as_ref()), h.as_ref()) {
+                                fail(m, "script does not match the hash");
+                                return;
+                            }
+                            match canonical::decode(s.as_ref()) {
+                                Ok(script) => call(ip, m, io, ctx, script),
+                                Err(e) => fail(m, &e)
+                            }
+                            return;
+                        }
+                    }
+                }
+                panic!()
+            },
+            CCLang::Split => {
+                if let Some(CCLang::Index(n)) = m.pop() {
+                    if let Some(CCLang::Index(k)) = m.pop() {
+                        if let Some(secret) = m.pop().and_then(Key::from) {
+                            // a negative count is out of range the same as zero
+                            let k = usize::try_from(k).unwrap_or(0);
+                            let n = usize::try_from(n).unwrap_or(0);
+                            match shamir::split_with(ctx.crypto(), secret.as_ref(), k, n) {
+                                Ok(shares) => {
+                                    for share in shares {
+                                        m.push(CCLang::Binary(share));
+                                    }
+                                    m.pushr(ip + 1);
+                                },
+                                Err(e) => fail(m, e)
+                            }
+                            return;
+                        }
+                    }
+                }
+                panic!()
+            },
+            CCLang::Combine => {
+                if let Some(CCLang::Index(k)) = m.pop() {
+                    if k > 255 {
+                        fail(m, "at most 255 shares can be combined");
+                        return;
+                    }
+                    let items = match take(m, k) {
+                        Ok(items) => items,
+                        Err(e) => {
+                            fail(m, e);
+                            return;
+                        }
+                    };
+                    let mut shares = Vec::new();
+                    for i in items {
+                        match i {
+                            CCLang::Binary(share) => shares.push(share),
+                            _ => {
+                                fail(m, "shares must be Binary");
+                                return;
+                            }
+                        }
+                    }
+                    // the recovered secret is key material
+                    match shamir::combine(&shares) {
+                        Ok(secret) => {
+                            m.push(CCLang::Secret(secret));
+                            m.pushr(ip + 1);
+                        },
+                        Err(e) => fail(m, e)
+                    }
+                    return;
+                }
+                panic!()
+            },
+            CCLang::Concat => {
+                match m.pop() {
+                    Some(CCLang::Binary(br)) => {
+                        if let Some(CCLang::Binary(bl)) = m.pop() {
+                            let mut buf = BytesMut::new();
+                            buf.extend_from_slice(&bl);
+                            buf.extend_from_slice(&br);
+                            m.push(CCLang::Binary(buf.freeze()));
+                            m.pushr(ip + 1);
+                            return;
+                        }
+                    },
+                    Some(CCLang::Text(tr)) => {
+                        if let Some(CCLang::Text(mut tl)) = m.pop() {
+                            tl.push_str(&tr);
+                            m.push(CCLang::Text(tl));
+                            m.pushr(ip + 1);
+                            return;
+                        }
+                    },
+                    _ => {}
+                }
+                panic!()
+            },
             CCLang::Slice => {
                 if let Some(CCLang::Index(end)) = m.pop() {
                     if let Some(CCLang::Index(begin)) = m.pop() {
@@ -945,6 +2443,91 @@ impl Instruction<CCLang> for CCLang {
                 }
                 panic!()
             },
+            CCLang::Pack => {
+                // ( x1..xn n -- list )
+                if let Some(CCLang::Index(n)) = m.pop() {
+                    match take(m, n) {
+                        Ok(items) => {
+                            m.push(CCLang::List(items));
+                            m.pushr(ip + 1);
+                        },
+                        Err(e) => fail(m, e)
+                    }
+                    return;
+                }
+                panic!()
+            },
+            CCLang::Unpack => {
+                // ( list -- x1..xn n )
+                if let Some(CCLang::List(items)) = m.pop() {
+                    let n = items.len() as isize;
+                    for i in items {
+                        m.push(i);
+                    }
+                    m.push(CCLang::Index(n));
+                    m.pushr(ip + 1);
+                    return;
+                }
+                panic!()
+            },
+            CCLang::Contains => {
+                // ( list a -- TRUE|FALSE )
+                if let Some(item) = m.pop() {
+                    if let Some(CCLang::List(items)) = m.pop() {
+                        m.push(CCLang::Boolean(has(&items, &item)));
+                        m.pushr(ip + 1);
+                        return;
+                    }
+                }
+                panic!()
+            },
+            CCLang::IntersectCount => {
+                // ( list list -- n )
+                if let Some(CCLang::List(r)) = m.pop() {
+                    if let Some(CCLang::List(l)) = m.pop() {
+                        // duplicates are only counted once so the same key
+                        // can't be counted twice towards a threshold
+                        let n = unique(l).iter().filter(|i| has(&r, i)).count();
+                        m.push(CCLang::Index(n as isize));
+                        m.pushr(ip + 1);
+                        return;
+                    }
+                }
+                panic!()
+            },
+            CCLang::Unique => {
+                // ( list -- list )
+                if let Some(CCLang::List(items)) = m.pop() {
+                    m.push(CCLang::List(unique(items)));
+                    m.pushr(ip + 1);
+                    return;
+                }
+                panic!()
+            },
+            CCLang::Secret(_) => {
+                m.push(self.clone());
+                m.pushr(ip + 1);
+            },
+            CCLang::ToSecret => {
+                match m.pop() {
+                    // the bytes of a Binary may be shared with the script and
+                    // other values so they are copied and the Binary isn't
+                    // wiped
+                    Some(CCLang::Binary(b)) => m.push(CCLang::Secret(Secret::from(b.as_ref()))),
+                    Some(CCLang::Text(t)) => m.push(CCLang::Secret(Secret::from(t.into_bytes()))),
+                    Some(CCLang::Secret(s)) => m.push(CCLang::Secret(s)),
+                    _ => panic!()
+                }
+                m.pushr(ip + 1);
+            },
+            CCLang::Reveal => {
+                if let Some(CCLang::Secret(s)) = m.pop() {
+                    m.push(CCLang::Binary(Bytes::copy_from_slice(s.as_ref())));
+                    m.pushr(ip + 1);
+                    return;
+                }
+                panic!()
+            },
             CCLang::Open => {
                 if let Err(e) = io.open(m) {
                     panic!(format!("{}", e));
@@ -991,6 +2574,252 @@ impl Instruction<CCLang> for CCLang {
                 }
                 panic!();
             },
+            CCLang::Swap => {
+                // ( x1 x2 -- x2 x1 )
+                shuffle(ip, m, 2, &[1, 0]);
+            },
+            CCLang::Over => {
+                // ( x1 x2 -- x1 x2 x1 )
+                shuffle(ip, m, 2, &[0, 1, 0]);
+            },
+            CCLang::Rot => {
+                // ( x1 x2 x3 -- x2 x3 x1 )
+                shuffle(ip, m, 3, &[1, 2, 0]);
+            },
+            CCLang::Nip => {
+                // ( x1 x2 -- x2 )
+                shuffle(ip, m, 2, &[1]);
+            },
+            CCLang::Tuck => {
+                // ( x1 x2 -- x2 x1 x2 )
+                shuffle(ip, m, 2, &[1, 0, 1]);
+            },
+            CCLang::Pick => {
+                // ( xn..x0 n -- xn..x0 xn )
+                if let Some(CCLang::Index(n)) = m.pop() {
+                    // checked before adding xn so that -1 isn't taken as 0
+                    if n < 0 {
+                        fail(m, "item count must not be negative");
+                        return;
+                    }
+                    match take(m, n.saturating_add(1)) {
+                        Ok(items) => {
+                            let xn = items[0].clone();
+                            for i in items {
+                                m.push(i);
+                            }
+                            m.push(xn);
+                            m.pushr(ip + 1);
+                        },
+                        Err(e) => fail(m, e)
+                    }
+                    return;
+                }
+                panic!()
+            },
+            CCLang::Roll => {
+                // ( xn..x0 n -- xn-1..x0 xn )
+                if let Some(CCLang::Index(n)) = m.pop() {
+                    // checked before adding xn so that -1 isn't taken as 0
+                    if n < 0 {
+                        fail(m, "item count must not be negative");
+                        return;
+                    }
+                    match take(m, n.saturating_add(1)) {
+                        Ok(mut items) => {
+                            let xn = items.remove(0);
+                            for i in items {
+                                m.push(i);
+                            }
+                            m.push(xn);
+                            m.pushr(ip + 1);
+                        },
+                        Err(e) => fail(m, e)
+                    }
+                    return;
+                }
+                panic!()
+            },
+            CCLang::Depth => {
+                // ( -- n )
+                let mut items = Vec::new();
+                while let Some(i) = m.pop() {
+                    items.push(i);
+                }
+                let n = items.len() as isize;
+                while let Some(i) = items.pop() {
+                    m.push(i);
+                }
+                m.push(CCLang::Index(n));
+                m.pushr(ip + 1);
+            },
+            CCLang::DropN => {
+                // ( x1..xn n -- )
+                if let Some(CCLang::Index(n)) = m.pop() {
+                    match take(m, n) {
+                        Ok(_) => m.pushr(ip + 1),
+                        Err(e) => fail(m, e)
+                    }
+                    return;
+                }
+                panic!()
+            },
+            CCLang::Len => {
+                // ( b|t|l -- b|t|l n )
+                let len = match m.pop() {
+                    Some(CCLang::Binary(b)) => {
+                        let len = b.len();
+                        m.push(CCLang::Binary(b));
+                        len
+                    },
+                    Some(CCLang::Text(t)) => {
+                        let len = t.len();
+                        m.push(CCLang::Text(t));
+                        len
+                    },
+                    Some(CCLang::Secret(s)) => {
+                        let len = s.len();
+                        m.push(CCLang::Secret(s));
+                        len
+                    },
+                    Some(CCLang::List(l)) => {
+                        let len = l.len();
+                        m.push(CCLang::List(l));
+                        len
+                    },
+                    Some(top) => {
+                        let e = format!("cannot take the length of {}", top.type_name());
+                        m.push(top);
+                        fail(m, &e);
+                        return;
+                    },
+                    None => {
+                        fail(m, "stack underflow");
+                        return;
+                    }
+                };
+                m.push(CCLang::Index(len as isize));
+                m.pushr(ip + 1);
+            },
+            CCLang::TypeOf => {
+                // ( a -- a t )
+                if let Some(top) = m.pop() {
+                    let t = top.type_name().to_string();
+                    m.push(top);
+                    m.push(CCLang::Text(t));
+                    m.pushr(ip + 1);
+                    return;
+                }
+                fail(m, "stack underflow")
+            },
+            CCLang::IsBinary => {
+                // ( a -- a TRUE|FALSE )
+                if let Some(top) = m.pop() {
+                    let b = matches!(top, CCLang::Binary(_));
+                    m.push(top);
+                    m.push(CCLang::Boolean(b));
+                    m.pushr(ip + 1);
+                    return;
+                }
+                fail(m, "stack underflow")
+            },
+            CCLang::IsText => {
+                // ( a -- a TRUE|FALSE )
+                if let Some(top) = m.pop() {
+                    let b = matches!(top, CCLang::Text(_));
+                    m.push(top);
+                    m.push(CCLang::Boolean(b));
+                    m.pushr(ip + 1);
+                    return;
+                }
+                fail(m, "stack underflow")
+            },
+            CCLang::IsBoolean => {
+                // ( a -- a TRUE|FALSE )
+                if let Some(top) = m.pop() {
+                    let b = matches!(top, CCLang::Boolean(_));
+                    m.push(top);
+                    m.push(CCLang::Boolean(b));
+                    m.pushr(ip + 1);
+                    return;
+                }
+                fail(m, "stack underflow")
+            },
+            CCLang::IsIndex => {
+                // ( a -- a TRUE|FALSE )
+                if let Some(top) = m.pop() {
+                    let b = matches!(top, CCLang::Index(_));
+                    m.push(top);
+                    m.push(CCLang::Boolean(b));
+                    m.pushr(ip + 1);
+                    return;
+                }
+                fail(m, "stack underflow")
+            },
+            CCLang::IsSecret => {
+                // ( a -- a TRUE|FALSE )
+                if let Some(top) = m.pop() {
+                    let b = matches!(top, CCLang::Secret(_));
+                    m.push(top);
+                    m.push(CCLang::Boolean(b));
+                    m.pushr(ip + 1);
+                    return;
+                }
+                fail(m, "stack underflow")
+            },
+            CCLang::GetVar => {
+                // ( name -- a )
+                if let Some(CCLang::Text(name)) = m.pop() {
+                    match ctx.var(&name) {
+                        Ok(v) => {
+                            m.push(v);
+                            m.pushr(ip + 1);
+                        },
+                        Err(e) => fail(m, &e)
+                    }
+                    return;
+                }
+                panic!()
+            },
+            CCLang::Now => {
+                // ( -- t )
+                match ctx.time() {
+                    Some(t) => {
+                        m.push(CCLang::Index(t));
+                        m.pushr(ip + 1);
+                    },
+                    None => fail(m, "the execution context has no time")
+                }
+            },
+            CCLang::CheckTime => {
+                // ( t -- TRUE|FALSE )
+                if let Some(CCLang::Index(t)) = m.pop() {
+                    match ctx.time() {
+                        Some(now) => {
+                            // like a lock time, true once the time is reached
+                            m.push(CCLang::Boolean(now >= t));
+                            m.pushr(ip + 1);
+                        },
+                        None => fail(m, "the execution context has no time")
+                    }
+                    return;
+                }
+                panic!()
+            },
+            CCLang::Assert => {
+                if let Some(CCLang::Boolean(b)) = m.pop() {
+                    if b {
+                        m.pushr(ip + 1);
+                    } else {
+                        // stop with FALSE on top so the script fails the
+                        // same way a failed check at the end of it would
+                        m.push(CCLang::Boolean(false));
+                        halt(m);
+                    }
+                    return;
+                }
+                panic!()
+            },
             CCLang::If => {
                 // find the location of the matching 'ELSE' if any and 'FI'
                 if let Some(ifm) = find_matching_elsefi(m, ip) {
@@ -1053,7 +2882,72 @@ impl Instruction<CCLang> for CCLang {
                     return;
                 }
                 panic!();
-            }
+            },
+            CCLang::Repeat => {
+                // the count must be the literal right before the 'REPEAT' so
+                // that the number of iterations is known before running
+                let literal = if ip > 0 { m.geti(ip - 1) } else { None };
+                match (m.pop(), literal) {
+                    (Some(CCLang::Index(n)), Some(CCLang::Index(l))) if n == l && n >= 0 => {
+                        // find the location of the matching 'LOOP'
+                        if let Some(endi) = find_matching_loop(m, ip) {
+                            if n > 0 {
+                                // record a frame with the start of the loop
+                                // body and the number of iterations left
+                                // after this one
+                                m.pushr(ip + 1);
+                                m.pushr((n - 1) as usize);
+
+                                // then tell the machine the correct next
+                                // instruction
+                                m.pushr(ip + 1);
+                            } else {
+                                // zero iterations so skip to the instruction
+                                // after the 'LOOP'. there is no frame.
+                                m.pushr(endi + 1);
+                            }
+                            return;
+                        }
+                        panic!()
+                    },
+                    _ => fail(m, "REPEAT count must be a literal Index")
+                }
+            },
+            CCLang::Loop => {
+                // we finished an iteration of a 'REPEAT' block so pop the
+                // frame and either go around again or continue after the loop
+                if let Some(remaining) = m.popr() {
+                    if let Some(body) = m.popr() {
+                        if remaining > 0 {
+                            m.pushr(body);
+                            m.pushr(remaining - 1);
+                            m.pushr(body);
+                        } else {
+                            m.pushr(ip + 1);
+                        }
+                        return;
+                    }
+                }
+                panic!();
+            },
+            CCLang::Return => {
+                // end the script here as if it had run off of the end,
+                // leaving the stack as the result
+                halt(m);
+                m.pushr(usize::MAX);
+            },
+            CCLang::Halt => {
+                // stop with FALSE on top the same as a failed 'ASSERT'
+                m.push(CCLang::Boolean(false));
+                halt(m);
+            },
+            CCLang::Define |
+            CCLang::EndDefine => {
+                // macros are expanded before a script is run, see
+                // Macros::expand
+                fail(m, "DEFINE must be expanded before running")
+            },
+            CCLang::Invoke => fail(m, "INVOKE must be expanded before running")
         }
     }
 }
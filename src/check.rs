@@ -0,0 +1,175 @@
+use bytes::Bytes;
+use crate::{
+    canonical,
+    policy::ExecutionPolicy,
+    CCLang,
+    Encoding,
+    Script
+};
+use std::collections::BTreeSet;
+
+// Static checks on a script before it is run. A script that passes has
+// balanced 'IF'/'ELSE'/'FI' and 'REPEAT'/'LOOP' blocks and every loop has a
+// literal iteration count, so it always terminates and the worst case number
+// of instructions it executes is known up front. Every 'GETVAR' has a literal
+// name so the inputs the host has to bind are known up front too. Every
+// 'CALLHASH' has a literal script that is checked along with the script that
+// calls it. Every algorithm the script names is built in and, when checked
+// against an execution policy, every instruction is allowed by it.
+
+/// What is known about a script before running it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Report {
+    /// The most instructions the script can execute
+    pub cost: u64,
+
+    /// The names of the inputs read with 'GETVAR', in sorted order
+    pub inputs: Vec<String>
+}
+
+/// Check the script, returning a report or the reason it is invalid
+pub fn check(script: &Script<CCLang>) -> Result<Report, String> {
+    check_with(script, &ExecutionPolicy::new())
+}
+
+/// Check the script and any script it calls against the execution policy as
+/// well
+pub fn check_with(script: &Script<CCLang>, policy: &ExecutionPolicy) -> Result<Report, String> {
+    let mut ip = 0;
+    while let Some(i) = script.get(ip) {
+        i.supported().map_err(|e| format!("{} at {}", e, ip))?;
+        if !policy.allows(&i) {
+            return Err(format!("{} at {} is not allowed by the execution policy", i, ip));
+        }
+        ip += 1;
+    }
+    let (cost, _, _) = block(script, 0, &[], policy)?;
+    let mut inputs = BTreeSet::new();
+    let mut ip = 0;
+    while let Some(i) = script.get(ip) {
+        if i == CCLang::GetVar {
+            match ip.checked_sub(1).and_then(|i| script.get(i)) {
+                Some(CCLang::Text(name)) => { inputs.insert(name); },
+                _ => return Err(format!("GETVAR at {} needs a literal name", ip))
+            }
+        }
+        if i == CCLang::CallHash {
+            inputs.extend(embedded(script, ip, policy)?.inputs);
+        }
+        ip += 1;
+    }
+    Ok(Report { cost, inputs: inputs.into_iter().collect() })
+}
+
+// the binary literal that ends at the given index, either as a Binary or as
+// the hex text it is written as, and the index it starts at
+fn binary(script: &Script<CCLang>, end: usize) -> Option<(Bytes, usize)> {
+    match script.get(end)? {
+        CCLang::Binary(b) => Some((b, end)),
+        CCLang::Decode => {
+            let start = end.checked_sub(2)?;
+            match (script.get(start)?, script.get(start + 1)?) {
+                (CCLang::Text(t), CCLang::EncodingId(Encoding::Hex)) => {
+                    hex::decode(t).ok().map(|b| (Bytes::from(b), start))
+                },
+                _ => None
+            }
+        },
+        _ => None
+    }
+}
+
+// check the script called by the 'CALLHASH' at ip
+fn embedded(script: &Script<CCLang>, ip: usize, policy: &ExecutionPolicy) -> Result<Report, String> {
+    let literal = || -> Option<(CCLang, Bytes, Bytes)> {
+        let id = script.get(ip.checked_sub(1)?)?;
+        let (h, start) = binary(script, ip.checked_sub(2)?)?;
+        let (s, _) = binary(script, start.checked_sub(1)?)?;
+        Some((id, h, s))
+    };
+    match literal() {
+        Some((CCLang::HashingId(id), h, s)) => {
+            if id.digest(s.as_ref()) != h {
+                return Err(format!("CALLHASH at {} does not match its hash", ip));
+            }
+            let called = canonical::decode(s.as_ref())
+                .map_err(|e| format!("CALLHASH at {}: {}", ip, e))?;
+            check_with(&called, policy).map_err(|e| format!("CALLHASH at {}: {}", ip, e))
+        },
+        _ => Err(format!("CALLHASH at {} needs a literal script", ip))
+    }
+}
+
+fn name(i: &CCLang) -> &'static str {
+    match i {
+        CCLang::Else => "ELSE",
+        CCLang::Fi => "FI",
+        _ => "LOOP"
+    }
+}
+
+// walk the instructions from start until one of the terminators or the end of
+// the script, returning the worst case cost, the index where it stopped and
+// the terminator found
+fn block(script: &Script<CCLang>, start: usize, terminators: &[CCLang], policy: &ExecutionPolicy) -> Result<(u64, usize, Option<CCLang>), String> {
+    let mut cost = 0u64;
+    let mut ip = start;
+    loop {
+        match script.get(ip) {
+            Some(CCLang::If) => {
+                // the 'IF' runs one of its branches followed by the 'ELSE'
+                // or the 'FI' that ends it
+                let (then, end, t) = block(script, ip + 1, &[CCLang::Else, CCLang::Fi], policy)?;
+                let (branch, end) = match t {
+                    Some(CCLang::Else) => {
+                        let (otherwise, end, _) = block(script, end + 1, &[CCLang::Fi], policy)?;
+                        (then.max(otherwise), end)
+                    },
+                    _ => (then, end)
+                };
+                cost = cost.saturating_add(branch).saturating_add(2);
+                ip = end + 1;
+            },
+            Some(CCLang::Repeat) => {
+                let n = match ip.checked_sub(1).and_then(|i| script.get(i)) {
+                    Some(CCLang::Index(n)) if n >= 0 => n as u64,
+                    _ => return Err(format!("REPEAT at {} needs a literal count", ip))
+                };
+                // each iteration runs the body and the 'LOOP'
+                let (body, end, _) = block(script, ip + 1, &[CCLang::Loop], policy)?;
+                cost = cost.saturating_add(n.saturating_mul(body.saturating_add(1))).saturating_add(1);
+                ip = end + 1;
+            },
+            Some(CCLang::CallHash) => {
+                // the called script runs in place of the 'CALLHASH'
+                let called = embedded(script, ip, policy)?;
+                cost = cost.saturating_add(called.cost).saturating_add(1);
+                ip += 1;
+            },
+            Some(CCLang::Define) |
+            Some(CCLang::EndDefine) |
+            Some(CCLang::Invoke) => {
+                return Err(format!("macros at {} must be expanded before checking", ip));
+            },
+            Some(i @ CCLang::Else) |
+            Some(i @ CCLang::Fi) |
+            Some(i @ CCLang::Loop) => {
+                if terminators.contains(&i) {
+                    return Ok((cost, ip, Some(i)));
+                }
+                return Err(format!("unexpected {} at {}", name(&i), ip));
+            },
+            Some(_) => {
+                cost = cost.saturating_add(1);
+                ip += 1;
+            },
+            None => {
+                if terminators.is_empty() {
+                    return Ok((cost, ip, None));
+                }
+                let missing: Vec<&str> = terminators.iter().map(name).collect();
+                return Err(format!("missing {} at end of script", missing.join(" or ")));
+            }
+        }
+    }
+}
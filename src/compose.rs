@@ -0,0 +1,116 @@
+use bytes::Bytes;
+use crate::{
+    check::check,
+    CCLang,
+    Script,
+    Signing
+};
+
+// Building bigger scripts out of smaller ones. Every part is checked on its
+// own before it is composed so that an unbalanced 'IF' in one part can't pair
+// up with a 'FI' in another and change what the composed script means, and
+// the composed script is checked again afterwards.
+//
+// Parts can't use 'RETURN' because it ends the whole composed script and not
+// just the part it is in, so "TRUE RETURN" would skip every check after it.
+
+/// Script composition
+pub trait Compose: Sized {
+    /// Run this script and then the next one
+    fn then(&self, next: &Self) -> Result<Self, String>;
+
+    /// TRUE if both scripts leave TRUE. The other script only runs if this
+    /// one leaves TRUE.
+    fn and(&self, other: &Self) -> Result<Self, String>;
+
+    /// TRUE if either script leaves TRUE. The other script only runs if this
+    /// one doesn't leave TRUE.
+    fn or(&self, other: &Self) -> Result<Self, String>;
+
+    /// Add another signature over the same data
+    fn append_parallel_signature(&self, signature: &Self) -> Result<Self, String>;
+
+    /// Add a signature that endorses the data and the endorsed signatures. The
+    /// endorsement script pushes the signature, public key and data, in that
+    /// order, and the endorsed signatures are appended to the data before it
+    /// is verified. See endorsement_message.
+    fn append_serial_endorsement(&self, endorsement: &Self, endorsed: &[Bytes], signing: Signing) -> Result<Self, String>;
+}
+
+/// The message signed by a serial endorsement: the data followed by the
+/// endorsed signatures
+pub fn endorsement_message(data: &[u8], endorsed: &[Bytes]) -> Vec<u8> {
+    let mut msg = data.to_vec();
+    for sig in endorsed {
+        msg.extend_from_slice(sig.as_ref());
+    }
+    msg
+}
+
+// the instructions in the script
+fn items(script: &Script<CCLang>) -> Vec<CCLang> {
+    let mut v = Vec::new();
+    while let Some(i) = script.get(v.len()) {
+        v.push(i);
+    }
+    v
+}
+
+// check the part and return its instructions
+fn part(script: &Script<CCLang>) -> Result<Vec<CCLang>, String> {
+    let v = items(script);
+    if v.contains(&CCLang::Return) {
+        return Err("cannot compose a script that uses RETURN".to_string());
+    }
+    check(script)?;
+    Ok(v)
+}
+
+// join the instructions into a script and check it
+fn compose(parts: Vec<Vec<CCLang>>) -> Result<Script<CCLang>, String> {
+    let script = Script::from(parts.concat());
+    check(&script)?;
+    Ok(script)
+}
+
+impl Compose for Script<CCLang> {
+    fn then(&self, next: &Self) -> Result<Self, String> {
+        compose(vec![part(self)?, part(next)?])
+    }
+
+    fn and(&self, other: &Self) -> Result<Self, String> {
+        // this IF other ELSE FALSE FI
+        compose(vec![
+            part(self)?,
+            vec![CCLang::If],
+            part(other)?,
+            vec![CCLang::Else, CCLang::Boolean(false), CCLang::Fi]
+        ])
+    }
+
+    fn or(&self, other: &Self) -> Result<Self, String> {
+        // this IF TRUE ELSE other FI
+        compose(vec![
+            part(self)?,
+            vec![CCLang::If, CCLang::Boolean(true), CCLang::Else],
+            part(other)?,
+            vec![CCLang::Fi]
+        ])
+    }
+
+    fn append_parallel_signature(&self, signature: &Self) -> Result<Self, String> {
+        self.and(signature)
+    }
+
+    fn append_serial_endorsement(&self, endorsement: &Self, endorsed: &[Bytes], signing: Signing) -> Result<Self, String> {
+        // endorsement <sig> CONCAT ... <id> VERIFY
+        let mut verify = part(endorsement)?;
+        for sig in endorsed {
+            verify.push(CCLang::Binary(sig.clone()));
+            verify.push(CCLang::Concat);
+        }
+        verify.push(CCLang::SigningId(signing));
+        verify.push(CCLang::Verify);
+        self.and(&Script::from(verify))
+    }
+}
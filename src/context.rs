@@ -0,0 +1,160 @@
+use crate::{
+    crypto::{
+        self,
+        CryptoProvider
+    },
+    policy::ExecutionPolicy,
+    AppIO,
+    CCLang,
+    Machine
+};
+use gsm::Stack;
+use std::{
+    collections::HashMap,
+    rc::Rc
+};
+
+// The execution context holds the values the host supplies to a script, such
+// as the current time, the named inputs read with 'GETVAR', the crypto
+// provider and the execution policy. The context runs the machine itself and
+// hands itself to every instruction it executes, so the same script gives the
+// same result for the same context, which keeps verification reproducible.
+//
+// A machine run on its own with Machine::execute gets an empty context: 'NOW',
+// 'CHECKTIME' and 'GETVAR' stop the script with an Error, the default crypto
+// provider is used and every opcode and algorithm is allowed.
+
+// the values a script can be given as inputs: the literals a script could
+// have been written with, and secrets
+fn data(v: &CCLang) -> bool {
+    match v {
+        CCLang::Boolean(_) |
+        CCLang::Binary(_) |
+        CCLang::Text(_) |
+        CCLang::Secret(_) |
+        CCLang::Index(_) |
+        CCLang::EncodingId(_) |
+        CCLang::EncryptionId(_) |
+        CCLang::SigningId(_) |
+        CCLang::HashingId(_) => true,
+        CCLang::List(l) => l.iter().all(data),
+        _ => false
+    }
+}
+
+/// Values supplied by the host for a script to use
+#[derive(Clone, Debug, Default)]
+pub struct Context {
+    now: Option<isize>,
+    vars: HashMap<String, CCLang>,
+    provider: Option<Rc<dyn CryptoProvider>>,
+    policy: ExecutionPolicy,
+    // how many 'CALLHASH' scripts deep the machine being run is
+    depth: usize
+}
+
+// contexts are equal when they share the same provider
+impl PartialEq for Context {
+    fn eq(&self, other: &Self) -> bool {
+        let provider = match (&self.provider, &other.provider) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false
+        };
+        self.now == other.now && self.vars == other.vars && provider && self.policy == other.policy
+    }
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Context::default()
+    }
+
+    /// Set the time, in seconds since the Unix epoch, pushed by 'NOW'
+    pub fn now(&mut self, t: isize) -> &mut Self {
+        self.now = Some(t);
+        self
+    }
+
+    /// Bind a named input for 'GETVAR' to push. Only data can be bound, and
+    /// an algorithm id must be built in and allowed by the policy; anything
+    /// else stops the script when 'GETVAR' reads it.
+    pub fn bind(&mut self, name: &str, value: CCLang) -> &mut Self {
+        self.vars.insert(name.to_string(), value);
+        self
+    }
+
+    /// Set the crypto provider used instead of the default backend
+    pub fn provider<P: CryptoProvider + 'static>(&mut self, p: P) -> &mut Self {
+        self.provider = Some(Rc::new(p));
+        self
+    }
+
+    /// Set the policy limiting the opcodes and algorithms a script may use
+    pub fn policy(&mut self, p: ExecutionPolicy) -> &mut Self {
+        self.policy = p;
+        self
+    }
+
+    /// Run the machine with this context, returning the stack when the script
+    /// ends or None if it stops early, the same as Machine::execute
+    pub fn execute(&self, m: &mut Machine<CCLang>, io: &dyn AppIO<CCLang>) -> Option<Stack<CCLang>> {
+        loop {
+            let ip = m.popr()?;
+            match m.geti(ip) {
+                Some(i) => i.step(ip, m, io, self),
+                None => {
+                    // past the end of the script, let the machine return its
+                    // stack
+                    m.pushr(ip);
+                    return m.execute(io);
+                }
+            }
+        }
+    }
+
+    // the time pushed by 'NOW'
+    pub(crate) fn time(&self) -> Option<isize> {
+        self.now
+    }
+
+    // the named input, as long as it is data that is built in and allowed by
+    // the policy. the host binds values without seeing the policy a script
+    // is run with, so they are checked when they are read.
+    pub(crate) fn var(&self, name: &str) -> Result<CCLang, String> {
+        let v = self.vars.get(name).ok_or_else(|| format!("input {} is not bound", name))?;
+        if !data(v) {
+            return Err(format!("input {} is not data", name));
+        }
+        v.supported().map_err(|e| e.to_string())?;
+        if !self.permits(v) {
+            return Err(format!("{} is not allowed by the execution policy", v));
+        }
+        Ok(v.clone())
+    }
+
+    // the crypto provider, or the default backend if none is set
+    pub(crate) fn crypto(&self) -> &dyn CryptoProvider {
+        match &self.provider {
+            Some(p) => p.as_ref(),
+            None => &crypto::DEFAULT
+        }
+    }
+
+    // whether the execution policy allows the instruction
+    pub(crate) fn permits(&self, i: &CCLang) -> bool {
+        self.policy.allows(i)
+    }
+
+    // how many 'CALLHASH' scripts deep the machine being run is
+    pub(crate) fn depth(&self) -> usize {
+        self.depth
+    }
+
+    // the context for a script called by 'CALLHASH'
+    pub(crate) fn called(&self) -> Context {
+        let mut ctx = self.clone();
+        ctx.depth += 1;
+        ctx
+    }
+}
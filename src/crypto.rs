@@ -0,0 +1,508 @@
+use bytes::Bytes;
+use crate::{
+    Encryption,
+    Hashing,
+    Secret,
+    Signing
+};
+#[cfg(feature = "backend-rustcrypto")]
+use rand::RngCore;
+#[cfg(feature = "backend-sodium")]
+use sodiumoxide::{
+    crypto::{
+        hash::{
+            sha256,
+            sha512
+        },
+        secretbox::xsalsa20poly1305,
+        sign
+    },
+    randombytes::randombytes_into
+};
+use std::fmt;
+
+// The crypto provider does the hashing, encryption, signing and random number
+// generation for the opcodes and the rest of the crate. The opcodes use the
+// provider in the execution Context that runs them and everything else uses
+// the default backend: Sodium if the backend-sodium feature is enabled,
+// otherwise RustCrypto. Every provider must give the same results for the
+// same inputs, so keys, ciphertexts and signatures use the libsodium formats:
+//
+//   XSalsa20Poly1305  32 byte key, 24 byte nonce, ciphertext is tag || data
+//   Ed25519           64 byte secret key (seed || public key), 32 byte public
+//                     key and 64 byte signatures
+//   secp256k1         32 byte secret key, 33 byte compressed public key and
+//                     64 byte r || s ECDSA signatures over the SHA256 of the
+//                     message
+//
+// libsodium has neither BLAKE3 nor secp256k1 so both providers use the blake3
+// and k256 crates for them.
+//
+// Errors are for malformed keys, nonces and signatures, for ciphertexts that
+// fail to authenticate and for algorithms that aren't built in. A well formed
+// signature that doesn't verify is not an error. Hashing can't fail, hashing
+// with an algorithm that isn't built in panics.
+
+/// The cryptographic primitives used by the crate
+pub trait CryptoProvider: fmt::Debug {
+    /// Hash the data
+    fn hash(&self, h: Hashing, data: &[u8]) -> Bytes;
+
+    /// Encrypt and authenticate the plaintext
+    fn seal(&self, e: Encryption, key: &[u8], nonce: &[u8], plaintext: &[u8]) -> Result<Bytes, String>;
+
+    /// Authenticate and decrypt the ciphertext
+    fn open(&self, e: Encryption, key: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, String>;
+
+    /// Sign the message with the secret key
+    fn sign(&self, s: Signing, sk: &[u8], msg: &[u8]) -> Result<Bytes, String>;
+
+    /// Check the signature over the message
+    fn verify(&self, s: Signing, pk: &[u8], msg: &[u8], sig: &[u8]) -> Result<bool, String>;
+
+    /// Derive the public and secret keys from the seed
+    fn keypair_from_seed(&self, s: Signing, seed: &[u8]) -> Result<(Bytes, Secret), String>;
+
+    /// Fill the buffer with random bytes
+    fn random(&self, buf: &mut [u8]);
+}
+
+// the RustCrypto provider only uses these for the algorithms built in
+#[cfg(any(feature = "backend-sodium", feature = "enc-xsalsa20"))]
+fn key_error(e: Encryption) -> String {
+    format!("{} key must be 32 bytes", e)
+}
+
+#[cfg(any(feature = "backend-sodium", feature = "enc-xsalsa20"))]
+fn nonce_error(e: Encryption) -> String {
+    format!("{} nonce must be 24 bytes", e)
+}
+
+#[cfg(any(feature = "backend-sodium", feature = "enc-xsalsa20"))]
+fn decrypt_error(e: Encryption) -> String {
+    format!("{} ciphertext failed to authenticate", e)
+}
+
+#[cfg(any(feature = "backend-sodium", feature = "sign-ed25519", feature = "sign-secp256k1"))]
+fn seed_error(s: Signing) -> String {
+    format!("{} seed must be 32 bytes", s)
+}
+
+#[cfg(any(feature = "backend-sodium", feature = "sign-ed25519"))]
+fn secret_key_error(s: Signing) -> String {
+    format!("{} secret key must be 64 bytes", s)
+}
+
+#[cfg(any(feature = "backend-sodium", feature = "sign-ed25519"))]
+fn public_key_error(s: Signing) -> String {
+    format!("{} public key must be 32 bytes", s)
+}
+
+#[cfg(any(feature = "backend-sodium", feature = "sign-ed25519", feature = "sign-secp256k1"))]
+fn signature_error(s: Signing) -> String {
+    format!("{} signature must be 64 bytes", s)
+}
+
+#[cfg(feature = "hash-blake3")]
+fn blake3(data: &[u8]) -> Bytes {
+    Bytes::copy_from_slice(blake3::hash(data).as_bytes())
+}
+
+#[cfg(not(feature = "hash-blake3"))]
+fn blake3(_: &[u8]) -> Bytes {
+    panic!("{}", crate::UnsupportedAlgorithm::new(Hashing::BLAKE3, "hash-blake3"))
+}
+
+#[cfg(feature = "sign-secp256k1")]
+mod secp256k1 {
+    use bytes::Bytes;
+    use crate::{
+        Secret,
+        Signing
+    };
+    use k256::ecdsa::{
+        signature::{
+            Signer,
+            Verifier
+        },
+        Signature,
+        SigningKey,
+        VerifyingKey
+    };
+
+    const S: Signing = Signing::Secp256k1;
+
+    pub fn sign(sk: &[u8], msg: &[u8]) -> Result<Bytes, String> {
+        let sk = SigningKey::from_slice(sk).map_err(|_| format!("{} secret key must be 32 bytes", S))?;
+        let sig: Signature = sk.sign(msg);
+        Ok(Bytes::copy_from_slice(&sig.to_bytes()))
+    }
+
+    pub fn verify(pk: &[u8], msg: &[u8], sig: &[u8]) -> Result<bool, String> {
+        let pk = VerifyingKey::from_sec1_bytes(pk).map_err(|_| format!("{} public key must be 33 bytes", S))?;
+        let sig = Signature::from_slice(sig).map_err(|_| super::signature_error(S))?;
+        Ok(pk.verify(msg, &sig).is_ok())
+    }
+
+    pub fn keypair_from_seed(seed: &[u8]) -> Result<(Bytes, Secret), String> {
+        // the seed is the secret key
+        let sk = SigningKey::from_slice(seed).map_err(|_| super::seed_error(S))?;
+        let pk = Bytes::copy_from_slice(sk.verifying_key().to_encoded_point(true).as_bytes());
+        Ok((pk, Secret::from(sk.to_bytes().to_vec())))
+    }
+}
+
+#[cfg(not(feature = "sign-secp256k1"))]
+mod secp256k1 {
+    use bytes::Bytes;
+    use crate::{
+        Secret,
+        Signing,
+        UnsupportedAlgorithm
+    };
+
+    fn unsupported() -> String {
+        UnsupportedAlgorithm::new(Signing::Secp256k1, "sign-secp256k1").to_string()
+    }
+
+    pub fn sign(_: &[u8], _: &[u8]) -> Result<Bytes, String> {
+        Err(unsupported())
+    }
+
+    pub fn verify(_: &[u8], _: &[u8], _: &[u8]) -> Result<bool, String> {
+        Err(unsupported())
+    }
+
+    pub fn keypair_from_seed(_: &[u8]) -> Result<(Bytes, Secret), String> {
+        Err(unsupported())
+    }
+}
+
+/// The libsodium provider
+#[cfg(feature = "backend-sodium")]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Sodium;
+
+#[cfg(feature = "backend-sodium")]
+impl CryptoProvider for Sodium {
+    fn hash(&self, h: Hashing, data: &[u8]) -> Bytes {
+        match h {
+            Hashing::SHA256 => Bytes::copy_from_slice(sha256::hash(data).as_ref()),
+            Hashing::SHA512 => Bytes::copy_from_slice(sha512::hash(data).as_ref()),
+            Hashing::BLAKE3 => blake3(data)
+        }
+    }
+
+    fn seal(&self, e: Encryption, key: &[u8], nonce: &[u8], plaintext: &[u8]) -> Result<Bytes, String> {
+        match e {
+            Encryption::XSalsa20Poly1305 => {
+                let key = xsalsa20poly1305::Key::from_slice(key).ok_or_else(|| key_error(e))?;
+                let nonce = xsalsa20poly1305::Nonce::from_slice(nonce).ok_or_else(|| nonce_error(e))?;
+                Ok(Bytes::from(xsalsa20poly1305::seal(plaintext, &nonce, &key)))
+            }
+        }
+    }
+
+    fn open(&self, e: Encryption, key: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, String> {
+        match e {
+            Encryption::XSalsa20Poly1305 => {
+                let key = xsalsa20poly1305::Key::from_slice(key).ok_or_else(|| key_error(e))?;
+                let nonce = xsalsa20poly1305::Nonce::from_slice(nonce).ok_or_else(|| nonce_error(e))?;
+                xsalsa20poly1305::open(ciphertext, &nonce, &key).map_err(|_| decrypt_error(e))
+            }
+        }
+    }
+
+    fn sign(&self, s: Signing, sk: &[u8], msg: &[u8]) -> Result<Bytes, String> {
+        match s {
+            Signing::Ed25519 => {
+                let sk = sign::SecretKey::from_slice(sk).ok_or_else(|| secret_key_error(s))?;
+                Ok(Bytes::copy_from_slice(sign::sign_detached(msg, &sk).as_ref()))
+            },
+            Signing::Secp256k1 => secp256k1::sign(sk, msg)
+        }
+    }
+
+    fn verify(&self, s: Signing, pk: &[u8], msg: &[u8], sig: &[u8]) -> Result<bool, String> {
+        match s {
+            Signing::Ed25519 => {
+                let pk = sign::PublicKey::from_slice(pk).ok_or_else(|| public_key_error(s))?;
+                let sig = sign::Signature::from_slice(sig).ok_or_else(|| signature_error(s))?;
+                Ok(sign::verify_detached(&sig, msg, &pk))
+            },
+            Signing::Secp256k1 => secp256k1::verify(pk, msg, sig)
+        }
+    }
+
+    fn keypair_from_seed(&self, s: Signing, seed: &[u8]) -> Result<(Bytes, Secret), String> {
+        match s {
+            Signing::Ed25519 => {
+                let seed = sign::Seed::from_slice(seed).ok_or_else(|| seed_error(s))?;
+                let (pk, sk) = sign::keypair_from_seed(&seed);
+                Ok((Bytes::copy_from_slice(pk.as_ref()), Secret::from(sk.as_ref())))
+            },
+            Signing::Secp256k1 => secp256k1::keypair_from_seed(seed)
+        }
+    }
+
+    fn random(&self, buf: &mut [u8]) {
+        randombytes_into(buf);
+    }
+}
+
+/// A pure Rust provider built on the RustCrypto crates
+#[cfg(feature = "backend-rustcrypto")]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RustCrypto;
+
+#[cfg(feature = "backend-rustcrypto")]
+impl CryptoProvider for RustCrypto {
+    fn hash(&self, h: Hashing, data: &[u8]) -> Bytes {
+        match h {
+            Hashing::SHA256 => sha::sha256(data),
+            Hashing::SHA512 => sha::sha512(data),
+            Hashing::BLAKE3 => blake3(data)
+        }
+    }
+
+    fn seal(&self, e: Encryption, key: &[u8], nonce: &[u8], plaintext: &[u8]) -> Result<Bytes, String> {
+        match e {
+            Encryption::XSalsa20Poly1305 => xsalsa20::seal(key, nonce, plaintext)
+        }
+    }
+
+    fn open(&self, e: Encryption, key: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, String> {
+        match e {
+            Encryption::XSalsa20Poly1305 => xsalsa20::open(key, nonce, ciphertext)
+        }
+    }
+
+    fn sign(&self, s: Signing, sk: &[u8], msg: &[u8]) -> Result<Bytes, String> {
+        match s {
+            Signing::Ed25519 => ed25519::sign(sk, msg),
+            Signing::Secp256k1 => secp256k1::sign(sk, msg)
+        }
+    }
+
+    fn verify(&self, s: Signing, pk: &[u8], msg: &[u8], sig: &[u8]) -> Result<bool, String> {
+        match s {
+            Signing::Ed25519 => ed25519::verify(pk, msg, sig),
+            Signing::Secp256k1 => secp256k1::verify(pk, msg, sig)
+        }
+    }
+
+    fn keypair_from_seed(&self, s: Signing, seed: &[u8]) -> Result<(Bytes, Secret), String> {
+        match s {
+            Signing::Ed25519 => ed25519::keypair_from_seed(seed),
+            Signing::Secp256k1 => secp256k1::keypair_from_seed(seed)
+        }
+    }
+
+    fn random(&self, buf: &mut [u8]) {
+        rand::rngs::OsRng.fill_bytes(buf);
+    }
+}
+
+#[cfg(all(feature = "backend-rustcrypto", feature = "hash-sha2"))]
+mod sha {
+    use bytes::Bytes;
+    use sha2::{
+        Digest,
+        Sha256,
+        Sha512
+    };
+
+    pub fn sha256(data: &[u8]) -> Bytes {
+        Bytes::copy_from_slice(&Sha256::digest(data))
+    }
+
+    pub fn sha512(data: &[u8]) -> Bytes {
+        Bytes::copy_from_slice(&Sha512::digest(data))
+    }
+}
+
+#[cfg(all(feature = "backend-rustcrypto", not(feature = "hash-sha2")))]
+mod sha {
+    use bytes::Bytes;
+    use crate::{
+        Hashing,
+        UnsupportedAlgorithm
+    };
+
+    pub fn sha256(_: &[u8]) -> Bytes {
+        panic!("{}", UnsupportedAlgorithm::new(Hashing::SHA256, "hash-sha2"))
+    }
+
+    pub fn sha512(_: &[u8]) -> Bytes {
+        panic!("{}", UnsupportedAlgorithm::new(Hashing::SHA512, "hash-sha2"))
+    }
+}
+
+#[cfg(all(feature = "backend-rustcrypto", feature = "enc-xsalsa20"))]
+mod xsalsa20 {
+    use bytes::Bytes;
+    use crate::Encryption;
+    use crypto_secretbox::{
+        aead::{
+            Aead,
+            KeyInit
+        },
+        XSalsa20Poly1305
+    };
+
+    const E: Encryption = Encryption::XSalsa20Poly1305;
+
+    fn cipher(key: &[u8], nonce: &[u8]) -> Result<XSalsa20Poly1305, String> {
+        if nonce.len() != 24 {
+            return Err(super::nonce_error(E));
+        }
+        XSalsa20Poly1305::new_from_slice(key).map_err(|_| super::key_error(E))
+    }
+
+    pub fn seal(key: &[u8], nonce: &[u8], plaintext: &[u8]) -> Result<Bytes, String> {
+        let cipher = cipher(key, nonce)?;
+        cipher.encrypt(nonce.into(), plaintext).map(Bytes::from).map_err(|_| super::key_error(E))
+    }
+
+    pub fn open(key: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, String> {
+        let cipher = cipher(key, nonce)?;
+        cipher.decrypt(nonce.into(), ciphertext).map_err(|_| super::decrypt_error(E))
+    }
+}
+
+#[cfg(all(feature = "backend-rustcrypto", not(feature = "enc-xsalsa20")))]
+mod xsalsa20 {
+    use bytes::Bytes;
+    use crate::{
+        Encryption,
+        UnsupportedAlgorithm
+    };
+
+    fn unsupported() -> String {
+        UnsupportedAlgorithm::new(Encryption::XSalsa20Poly1305, "enc-xsalsa20").to_string()
+    }
+
+    pub fn seal(_: &[u8], _: &[u8], _: &[u8]) -> Result<Bytes, String> {
+        Err(unsupported())
+    }
+
+    pub fn open(_: &[u8], _: &[u8], _: &[u8]) -> Result<Vec<u8>, String> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(all(feature = "backend-rustcrypto", feature = "sign-ed25519"))]
+mod ed25519 {
+    use bytes::Bytes;
+    use crate::{
+        Secret,
+        Signing
+    };
+    use ed25519_dalek::{
+        Signature,
+        Signer,
+        SigningKey,
+        VerifyingKey
+    };
+    use std::convert::TryInto;
+
+    const S: Signing = Signing::Ed25519;
+
+    pub fn sign(sk: &[u8], msg: &[u8]) -> Result<Bytes, String> {
+        let sk: &[u8; 64] = sk.try_into().map_err(|_| super::secret_key_error(S))?;
+        let sk = SigningKey::from_keypair_bytes(sk).map_err(|_| super::secret_key_error(S))?;
+        Ok(Bytes::copy_from_slice(&sk.sign(msg).to_bytes()))
+    }
+
+    pub fn verify(pk: &[u8], msg: &[u8], sig: &[u8]) -> Result<bool, String> {
+        let pk: &[u8; 32] = pk.try_into().map_err(|_| super::public_key_error(S))?;
+        let sig = Signature::from_slice(sig).map_err(|_| super::signature_error(S))?;
+        // libsodium rejects small order keys and signatures, so only
+        // the strict verification gives the same answers
+        match VerifyingKey::from_bytes(pk) {
+            Ok(pk) => Ok(pk.verify_strict(msg, &sig).is_ok()),
+            // a point that isn't on the curve can't verify anything
+            Err(_) => Ok(false)
+        }
+    }
+
+    pub fn keypair_from_seed(seed: &[u8]) -> Result<(Bytes, Secret), String> {
+        let seed: &[u8; 32] = seed.try_into().map_err(|_| super::seed_error(S))?;
+        let sk = SigningKey::from_bytes(seed);
+        let pk = Bytes::copy_from_slice(sk.verifying_key().as_bytes());
+        Ok((pk, Secret::from(sk.to_keypair_bytes().as_ref())))
+    }
+}
+
+#[cfg(all(feature = "backend-rustcrypto", not(feature = "sign-ed25519")))]
+mod ed25519 {
+    use bytes::Bytes;
+    use crate::{
+        Secret,
+        Signing,
+        UnsupportedAlgorithm
+    };
+
+    fn unsupported() -> String {
+        UnsupportedAlgorithm::new(Signing::Ed25519, "sign-ed25519").to_string()
+    }
+
+    pub fn sign(_: &[u8], _: &[u8]) -> Result<Bytes, String> {
+        Err(unsupported())
+    }
+
+    pub fn verify(_: &[u8], _: &[u8], _: &[u8]) -> Result<bool, String> {
+        Err(unsupported())
+    }
+
+    pub fn keypair_from_seed(_: &[u8]) -> Result<(Bytes, Secret), String> {
+        Err(unsupported())
+    }
+}
+
+// the provider used when the Context doesn't set one
+#[cfg(feature = "backend-sodium")]
+pub(crate) const DEFAULT: Sodium = Sodium;
+
+#[cfg(all(feature = "backend-rustcrypto", not(feature = "backend-sodium")))]
+pub(crate) const DEFAULT: RustCrypto = RustCrypto;
+
+// without a backend the crate stops at the compile_error in lib.rs, this only
+// keeps the uses of DEFAULT from adding errors of their own
+#[cfg(not(any(feature = "backend-sodium", feature = "backend-rustcrypto")))]
+#[derive(Debug)]
+pub(crate) struct NoBackend;
+
+#[cfg(not(any(feature = "backend-sodium", feature = "backend-rustcrypto")))]
+impl CryptoProvider for NoBackend {
+    fn hash(&self, _: Hashing, _: &[u8]) -> Bytes {
+        unreachable!()
+    }
+
+    fn seal(&self, _: Encryption, _: &[u8], _: &[u8], _: &[u8]) -> Result<Bytes, String> {
+        unreachable!()
+    }
+
+    fn open(&self, _: Encryption, _: &[u8], _: &[u8], _: &[u8]) -> Result<Vec<u8>, String> {
+        unreachable!()
+    }
+
+    fn sign(&self, _: Signing, _: &[u8], _: &[u8]) -> Result<Bytes, String> {
+        unreachable!()
+    }
+
+    fn verify(&self, _: Signing, _: &[u8], _: &[u8], _: &[u8]) -> Result<bool, String> {
+        unreachable!()
+    }
+
+    fn keypair_from_seed(&self, _: Signing, _: &[u8]) -> Result<(Bytes, Secret), String> {
+        unreachable!()
+    }
+
+    fn random(&self, _: &mut [u8]) {
+        unreachable!()
+    }
+}
+
+#[cfg(not(any(feature = "backend-sodium", feature = "backend-rustcrypto")))]
+pub(crate) const DEFAULT: NoBackend = NoBackend;
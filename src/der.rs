@@ -0,0 +1,142 @@
+use crate::{
+    crypto::{
+        self,
+        CryptoProvider
+    },
+    Secret,
+    Signing
+};
+
+// Just enough DER to unwrap the key containers that OpenSSL and HSMs export:
+//
+//   SubjectPublicKeyInfo ::= SEQUENCE {
+//       algorithm        AlgorithmIdentifier,
+//       subjectPublicKey BIT STRING }
+//
+//   OneAsymmetricKey ::= SEQUENCE {
+//       version          INTEGER,
+//       algorithm        AlgorithmIdentifier,
+//       privateKey       OCTET STRING,
+//       attributes   [0] IMPLICIT Attributes OPTIONAL,
+//       publicKey    [1] IMPLICIT BIT STRING OPTIONAL }
+//
+//   AlgorithmIdentifier ::= SEQUENCE {
+//       algorithm        OBJECT IDENTIFIER,
+//       parameters       ANY OPTIONAL }
+//
+// PKCS#8 PrivateKeyInfo is version 0 of OneAsymmetricKey (RFC 5958).
+
+const INTEGER: u8 = 0x02;
+const BIT_STRING: u8 = 0x03;
+const OCTET_STRING: u8 = 0x04;
+const OID: u8 = 0x06;
+const SEQUENCE: u8 = 0x30;
+
+// 1.3.101.112 from RFC 8410
+const OID_ED25519: &[u8] = &[0x2b, 0x65, 0x70];
+
+/// A key unwrapped from its container
+pub enum Key {
+    Public(Signing, Vec<u8>),
+    Private(Signing, Secret)
+}
+
+// read one tag-length-value from the front of the buffer, returning the value
+// and whatever follows it
+fn tlv(tag: u8, buf: &[u8]) -> Result<(&[u8], &[u8]), &'static str> {
+    if buf.len() < 2 || buf[0] != tag {
+        return Err("unexpected DER tag");
+    }
+    let (len, hdr) = match buf[1] {
+        l if l < 0x80 => (l as usize, 2),
+        0x81 => {
+            if buf.len() < 3 || buf[2] < 0x80 {
+                return Err("invalid DER length");
+            }
+            (buf[2] as usize, 3)
+        },
+        0x82 => {
+            if buf.len() < 4 || buf[2] == 0 {
+                return Err("invalid DER length");
+            }
+            (((buf[2] as usize) << 8) | buf[3] as usize, 4)
+        },
+        _ => return Err("invalid DER length")
+    };
+    if buf.len() - hdr < len {
+        return Err("truncated DER value");
+    }
+    Ok((&buf[hdr..hdr + len], &buf[hdr + len..]))
+}
+
+fn algorithm(buf: &[u8]) -> Result<(Signing, &[u8]), &'static str> {
+    let (alg, rest) = tlv(SEQUENCE, buf)?;
+    let (oid, params) = tlv(OID, alg)?;
+    if oid == OID_ED25519 && Signing::Ed25519.supported().is_ok() {
+        // RFC 8410 requires the parameters to be absent
+        if !params.is_empty() {
+            return Err("unexpected Ed25519 parameters");
+        }
+        return Ok((Signing::Ed25519, rest));
+    }
+    Err("unsupported key algorithm")
+}
+
+/// Unwrap a DER encoded SubjectPublicKeyInfo or PKCS#8 private key
+pub fn unwrap_key(der: &[u8]) -> Result<Key, &'static str> {
+    unwrap_key_with(&crypto::DEFAULT, der)
+}
+
+// unwrap the key, deriving the keypair of a private key with the provider
+pub(crate) fn unwrap_key_with(p: &dyn CryptoProvider, der: &[u8]) -> Result<Key, &'static str> {
+    let (body, rest) = tlv(SEQUENCE, der)?;
+    if !rest.is_empty() {
+        return Err("trailing data after DER key");
+    }
+
+    // a private key starts with its version, a public key with its algorithm
+    if body.first() == Some(&INTEGER) {
+        let (version, rest) = tlv(INTEGER, body)?;
+        if version != [0] && version != [1] {
+            return Err("unsupported private key version");
+        }
+        let (id, rest) = algorithm(rest)?;
+        // the optional attributes and public key are ignored, the public key
+        // is derived from the private key anyway
+        let (key, _) = tlv(OCTET_STRING, rest)?;
+        match id {
+            Signing::Ed25519 => {
+                // CurvePrivateKey ::= OCTET STRING
+                let (seed, rest) = tlv(OCTET_STRING, key)?;
+                if !rest.is_empty() {
+                    return Err("trailing data after Ed25519 private key");
+                }
+                let (_, sk) = p.keypair_from_seed(id, seed)
+                    .map_err(|_| "Ed25519 private key must be 32 bytes")?;
+                Ok(Key::Private(id, sk))
+            },
+            Signing::Secp256k1 => Err("unsupported key algorithm")
+        }
+    } else {
+        let (id, rest) = algorithm(body)?;
+        let (bits, rest) = tlv(BIT_STRING, rest)?;
+        if !rest.is_empty() {
+            return Err("trailing data after public key");
+        }
+        // the first byte of a BIT STRING is the number of unused bits
+        match bits.split_first() {
+            Some((0, key)) => {
+                match id {
+                    Signing::Ed25519 => {
+                        if key.len() != 32 {
+                            return Err("Ed25519 public key must be 32 bytes");
+                        }
+                        Ok(Key::Public(id, key.to_vec()))
+                    },
+                    Signing::Secp256k1 => Err("unsupported key algorithm")
+                }
+            },
+            _ => Err("invalid public key BIT STRING")
+        }
+    }
+}
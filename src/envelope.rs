@@ -0,0 +1,202 @@
+use bytes::Bytes;
+use crate::{
+    canonical,
+    crypto::{
+        self,
+        CryptoProvider
+    },
+    multiformats,
+    secret::memcmp,
+    CCLang,
+    Hashing,
+    Script,
+    Secret,
+    Signing
+};
+use serde::{
+    de::{
+        self,
+        IntoDeserializer
+    },
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer
+};
+
+// A signed script envelope carries a script in its canonical form along with
+// its id and signatures over the id. The signatures cover the multihash of the
+// id so they also commit to the hashing algorithm. The script is only handed
+// back after the id and every signature check out, so a policy script can be
+// distributed without trusting the channel it came over.
+//
+// In JSON an envelope looks like:
+//
+//   {
+//     "script": "<canonical form hex>",
+//     "hashing": "SHA256",
+//     "id": "<id hex>",
+//     "signatures": [
+//       { "signing": "Ed25519", "key": "<public key hex>", "signature": "<signature hex>" }
+//     ]
+//   }
+
+/// A signature over an envelope
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnvelopeSignature {
+    pub signing: Signing,
+    pub public_key: Bytes,
+    pub signature: Bytes
+}
+
+/// A script with its id and the signatures over it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Envelope {
+    script: Bytes,
+    hashing: Hashing,
+    id: Bytes,
+    signatures: Vec<EnvelopeSignature>
+}
+
+impl Envelope {
+    /// Wrap the script in an envelope with no signatures
+    pub fn new(script: &Script<CCLang>, hashing: Hashing) -> Result<Self, String> {
+        Envelope::new_with(&crypto::DEFAULT, script, hashing)
+    }
+
+    /// Wrap the script in an envelope, hashing it with the crypto provider
+    pub fn new_with(p: &dyn CryptoProvider, script: &Script<CCLang>, hashing: Hashing) -> Result<Self, String> {
+        hashing.supported().map_err(|e| e.to_string())?;
+        let script = canonical::encode(script)?;
+        let id = hashing.digest_with(p, &script);
+        Ok(Envelope { script, hashing, id, signatures: Vec::new() })
+    }
+
+    /// The id of the script in the envelope
+    pub fn id(&self) -> &Bytes {
+        &self.id
+    }
+
+    /// The signatures over the envelope
+    pub fn signatures(&self) -> &[EnvelopeSignature] {
+        &self.signatures
+    }
+
+    // the message that is signed
+    fn message(&self) -> Result<Bytes, String> {
+        Ok(multiformats::wrap_multihash(self.hashing, &self.id)?)
+    }
+
+    /// Sign the envelope with the secret key
+    pub fn sign(&mut self, signing: Signing, sk: &Secret) -> Result<&mut Self, String> {
+        self.sign_with(&crypto::DEFAULT, signing, sk)
+    }
+
+    /// Sign the envelope with the secret key using the crypto provider
+    pub fn sign_with(&mut self, p: &dyn CryptoProvider, signing: Signing, sk: &Secret) -> Result<&mut Self, String> {
+        signing.supported().map_err(|e| e.to_string())?;
+        let msg = self.message()?;
+        let signature = p.sign(signing, sk.as_ref(), &msg)?;
+        let public_key = match signing {
+            // the public key is the second half of the secret key
+            Signing::Ed25519 => Bytes::copy_from_slice(&sk.as_ref()[32..]),
+            // the secret key is the seed
+            Signing::Secp256k1 => p.keypair_from_seed(signing, sk.as_ref())?.0
+        };
+        self.signatures.push(EnvelopeSignature { signing, public_key, signature });
+        Ok(self)
+    }
+
+    /// Check the envelope and return the script in it. Every signature must
+    /// be valid and at least threshold of the trusted keys must have signed.
+    /// The threshold must be at least 1 and there must be trusted keys.
+    pub fn verify(&self, trusted: &[Bytes], threshold: usize) -> Result<Script<CCLang>, String> {
+        self.verify_with(&crypto::DEFAULT, trusted, threshold)
+    }
+
+    /// Check the envelope and return the script in it using the crypto
+    /// provider
+    pub fn verify_with(&self, p: &dyn CryptoProvider, trusted: &[Bytes], threshold: usize) -> Result<Script<CCLang>, String> {
+        // an envelope nobody has to sign proves nothing
+        if threshold == 0 {
+            return Err("envelope threshold must be at least 1".to_string());
+        }
+        if trusted.is_empty() {
+            return Err("envelope needs at least one trusted key".to_string());
+        }
+        let script = canonical::decode(&self.script)?;
+        if !memcmp(&self.hashing.digest_with(p, &self.script), &self.id) {
+            return Err("envelope id does not match the script".to_string());
+        }
+        let msg = self.message()?;
+        let mut signers: Vec<&Bytes> = Vec::new();
+        for s in &self.signatures {
+            let valid = p.verify(s.signing, &s.public_key, &msg, &s.signature);
+            let valid = valid.unwrap_or(false);
+            if !valid {
+                return Err("envelope has an invalid signature".to_string());
+            }
+            if trusted.contains(&s.public_key) && !signers.contains(&&s.public_key) {
+                signers.push(&s.public_key);
+            }
+        }
+        if signers.len() < threshold {
+            return Err(format!("envelope has {} of the {} required trusted signatures", signers.len(), threshold));
+        }
+        Ok(script)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireSignature {
+    signing: String,
+    key: String,
+    signature: String
+}
+
+#[derive(Serialize, Deserialize)]
+struct Wire {
+    script: String,
+    hashing: String,
+    id: String,
+    signatures: Vec<WireSignature>
+}
+
+impl Serialize for Envelope {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        Wire {
+            script: hex::encode(&self.script),
+            hashing: self.hashing.to_string(),
+            id: hex::encode(&self.id),
+            signatures: self.signatures.iter().map(|sig| WireSignature {
+                signing: sig.signing.to_string(),
+                key: hex::encode(&sig.public_key),
+                signature: hex::encode(&sig.signature)
+            }).collect()
+        }.serialize(s)
+    }
+}
+
+fn from_hex<E: de::Error>(s: &str) -> Result<Bytes, E> {
+    hex::decode(s).map(Bytes::from).map_err(E::custom)
+}
+
+impl<'de> Deserialize<'de> for Envelope {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Envelope, D::Error> {
+        let w = Wire::deserialize(d)?;
+        let mut signatures = Vec::new();
+        for sig in w.signatures {
+            signatures.push(EnvelopeSignature {
+                signing: Signing::deserialize(sig.signing.into_deserializer())?,
+                public_key: from_hex(&sig.key)?,
+                signature: from_hex(&sig.signature)?
+            });
+        }
+        Ok(Envelope {
+            script: from_hex(&w.script)?,
+            hashing: Hashing::deserialize(w.hashing.into_deserializer())?,
+            id: from_hex(&w.id)?,
+            signatures
+        })
+    }
+}
@@ -9,6 +9,7 @@ use crate::{
         Handle,
         Index,
         Mode,
+        Secret,
         Text,
         Whence
     },
@@ -178,6 +179,7 @@ impl AppIO<CCLang> for FileIO {
                 }
                 return Ok(())
             }
+            Some(Secret(_)) => return Err(io::Error::new(io::ErrorKind::InvalidData, "writing a Secret requires REVEAL")),
             _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "no data to write"))
         }
     }
@@ -1,20 +1,79 @@
+#[cfg(not(any(feature = "backend-sodium", feature = "backend-rustcrypto")))]
+compile_error!("either the backend-sodium or the backend-rustcrypto feature must be enabled");
+
 pub mod cclang;
 pub use crate::cclang::{
 	Encoding,
 	Encryption,
 	Signing,
 	Hashing,
-	CCLang
+	CCLang,
+	UnsupportedAlgorithm
+};
+pub mod canonical;
+pub use crate::canonical::{
+	ScriptId
+};
+pub mod check;
+pub use crate::check::{
+	Report
+};
+pub mod compose;
+pub use crate::compose::{
+	Compose
+};
+pub mod context;
+pub use crate::context::{
+	Context
+};
+pub mod crypto;
+pub use crate::crypto::{
+	CryptoProvider
+};
+#[cfg(feature = "backend-rustcrypto")]
+pub use crate::crypto::{
+	RustCrypto
 };
+#[cfg(feature = "backend-sodium")]
+pub use crate::crypto::{
+	Sodium
+};
+pub mod der;
+pub mod envelope;
+pub use crate::envelope::{
+	Envelope,
+	EnvelopeSignature
+};
+#[cfg(feature = "fileio")]
 pub mod fileio;
+#[cfg(feature = "fileio")]
 pub use crate::fileio::{
 	FileHandle,
 	FileIO
 };
+pub mod macros;
+pub use crate::macros::{
+	Macros
+};
+pub mod merkle;
+pub mod multiformats;
 pub mod nullio;
 pub use crate::nullio::{
 	NullIO
 };
+pub mod outcome;
+pub use crate::outcome::{
+	ScriptOutcome
+};
+pub mod policy;
+pub use crate::policy::{
+	ExecutionPolicy
+};
+pub mod secret;
+pub use crate::secret::{
+	Secret
+};
+pub mod shamir;
 
 // re-export GSM types
 pub use gsm::{
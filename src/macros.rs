@@ -0,0 +1,156 @@
+use crate::{
+    CCLang,
+    Machine,
+    Script
+};
+use std::collections::HashMap;
+
+// Macros are defined with 'DEFINE name ... ENDDEF' and invoked with
+// 'INVOKE name'. Expansion is a pure transformation from a script to a script
+// without any macros in it, done before the script is checked or run, so a
+// signed script can always be audited in its fully expanded form.
+//
+// A macro body is expanded when the macro is defined, using only the macros
+// defined before it. Recursion, direct or otherwise, is impossible because a
+// macro can't refer to itself or to a macro defined after it. Each level of
+// macros can still double the size of the script, so a macro body and an
+// expanded script are limited to MAX_EXPANDED instructions.
+//
+// Only the name following an 'INVOKE' is looked up, so text that happens to
+// equal a macro name, such as a 'GETVAR' name, is left as it is.
+
+/// The most instructions a macro body or an expanded script can hold
+pub const MAX_EXPANDED: usize = 65536;
+
+// add the instructions to the expansion as long as it stays within
+// MAX_EXPANDED
+fn extend(out: &mut Vec<CCLang>, items: &[CCLang]) -> Result<(), String> {
+    if out.len().saturating_add(items.len()) > MAX_EXPANDED {
+        return Err(format!("macro expansion is longer than {} instructions", MAX_EXPANDED));
+    }
+    out.extend_from_slice(items);
+    Ok(())
+}
+
+/// A table of macro definitions
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Macros {
+    defs: HashMap<String, Vec<CCLang>>
+}
+
+impl Macros {
+    pub fn new() -> Self {
+        Macros::default()
+    }
+
+    /// Preload the definitions from a trusted library script. The library
+    /// must contain nothing but definitions.
+    pub fn load(&mut self, library: &Script<CCLang>) -> Result<&mut Self, String> {
+        let rest = self.expand_into(library)?;
+        if !rest.is_empty() {
+            return Err("library script must only contain definitions".to_string());
+        }
+        Ok(self)
+    }
+
+    /// Define a macro
+    pub fn define(&mut self, name: &str, body: &[CCLang]) -> Result<&mut Self, String> {
+        if self.defs.contains_key(name) {
+            return Err(format!("macro {} is already defined", name));
+        }
+        let mut expanded = Vec::new();
+        let mut i = 0;
+        while let Some(item) = body.get(i) {
+            match item {
+                CCLang::Invoke => {
+                    let called = match body.get(i + 1) {
+                        Some(CCLang::Text(called)) => called,
+                        _ => return Err(format!("INVOKE in macro {} must be followed by a name", name))
+                    };
+                    if called == name {
+                        return Err(format!("macro {} is recursive", name));
+                    }
+                    self.invoke(called, &mut expanded)?;
+                    i += 2;
+                },
+                CCLang::Define | CCLang::EndDefine => {
+                    return Err(format!("macro {} contains a definition", name));
+                },
+                _ => {
+                    extend(&mut expanded, std::slice::from_ref(item))?;
+                    i += 1;
+                }
+            }
+        }
+        self.defs.insert(name.to_string(), expanded);
+        Ok(self)
+    }
+
+    /// True if the named macro is defined
+    pub fn contains(&self, name: &str) -> bool {
+        self.defs.contains_key(name)
+    }
+
+    /// Expand the script. Definitions in the script are added to a copy of
+    /// this table and only apply to the rest of the script.
+    pub fn expand(&self, script: &Script<CCLang>) -> Result<Script<CCLang>, String> {
+        let mut table = self.clone();
+        Ok(Script::from(table.expand_into(script)?))
+    }
+
+    /// Expand the script and build a machine to run it
+    pub fn machine(&self, script: &Script<CCLang>) -> Result<Machine<CCLang>, String> {
+        Ok(Machine::from(self.expand(script)?))
+    }
+
+    fn invoke(&self, name: &str, out: &mut Vec<CCLang>) -> Result<(), String> {
+        match self.defs.get(name) {
+            Some(body) => extend(out, body),
+            None => Err(format!("macro {} is not defined", name))
+        }
+    }
+
+    // add the definitions in the script to this table and return the rest of
+    // the script expanded
+    fn expand_into(&mut self, script: &Script<CCLang>) -> Result<Vec<CCLang>, String> {
+        let mut out = Vec::new();
+        let mut ip = 0;
+        while let Some(i) = script.get(ip) {
+            match i {
+                CCLang::Define => {
+                    let name = match script.get(ip + 1) {
+                        Some(CCLang::Text(name)) => name,
+                        _ => return Err(format!("DEFINE at {} must be followed by a name", ip))
+                    };
+                    let mut body = Vec::new();
+                    let mut end = ip + 2;
+                    loop {
+                        match script.get(end) {
+                            Some(CCLang::EndDefine) => break,
+                            Some(i) => body.push(i),
+                            None => return Err(format!("missing ENDDEF for DEFINE at {}", ip))
+                        }
+                        end += 1;
+                    }
+                    self.define(&name, &body)?;
+                    ip = end + 1;
+                },
+                CCLang::EndDefine => {
+                    return Err(format!("unexpected ENDDEF at {}", ip));
+                },
+                CCLang::Invoke => {
+                    match script.get(ip + 1) {
+                        Some(CCLang::Text(name)) => self.invoke(&name, &mut out)?,
+                        _ => return Err(format!("INVOKE at {} must be followed by a name", ip))
+                    }
+                    ip += 2;
+                },
+                _ => {
+                    extend(&mut out, &[i])?;
+                    ip += 1;
+                }
+            }
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,138 @@
+use bytes::{
+    BufMut,
+    Bytes,
+    BytesMut
+};
+use crate::{
+    crypto::{
+        self,
+        CryptoProvider
+    },
+    Hashing
+};
+
+// Merkle trees follow RFC 6962 section 2.1. Leaves and interior nodes are
+// hashed with different prefixes so that a leaf can never be passed off as an
+// interior node:
+//
+//   leaf hash = H(0x00 || leaf)
+//   node hash = H(0x01 || left || right)
+//
+// The tree is not padded. A tree with n > 1 leaves is split at the largest
+// power of two less than n, so the root of an empty tree is H("") and inclusion
+// proofs depend on the tree size as well as the leaf index.
+//
+// The opcodes hash with the crypto provider in the execution context, the
+// public functions here with the default provider.
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Hash a leaf of the tree
+pub fn leaf_hash(h: Hashing, leaf: &[u8]) -> Bytes {
+    leaf_hash_with(&crypto::DEFAULT, h, leaf)
+}
+
+fn leaf_hash_with(p: &dyn CryptoProvider, h: Hashing, leaf: &[u8]) -> Bytes {
+    let mut buf = BytesMut::with_capacity(leaf.len() + 1);
+    buf.put_u8(LEAF_PREFIX);
+    buf.put_slice(leaf);
+    p.hash(h, &buf)
+}
+
+/// Hash an interior node from its left and right children
+pub fn node_hash(h: Hashing, left: &[u8], right: &[u8]) -> Bytes {
+    node_hash_with(&crypto::DEFAULT, h, left, right)
+}
+
+fn node_hash_with(p: &dyn CryptoProvider, h: Hashing, left: &[u8], right: &[u8]) -> Bytes {
+    let mut buf = BytesMut::with_capacity(left.len() + right.len() + 1);
+    buf.put_u8(NODE_PREFIX);
+    buf.put_slice(left);
+    buf.put_slice(right);
+    p.hash(h, &buf)
+}
+
+// the largest power of two smaller than n, n must be > 1
+fn split_point(n: usize) -> usize {
+    let mut k = 1;
+    while k << 1 < n {
+        k <<= 1;
+    }
+    k
+}
+
+/// Calculate the root of the tree over the leaves
+pub fn root(h: Hashing, leaves: &[Bytes]) -> Bytes {
+    root_with(&crypto::DEFAULT, h, leaves)
+}
+
+pub(crate) fn root_with(p: &dyn CryptoProvider, h: Hashing, leaves: &[Bytes]) -> Bytes {
+    match leaves.len() {
+        0 => p.hash(h, &[]),
+        1 => leaf_hash_with(p, h, leaves[0].as_ref()),
+        n => {
+            let k = split_point(n);
+            node_hash_with(p, h, &root_with(p, h, &leaves[..k]), &root_with(p, h, &leaves[k..]))
+        }
+    }
+}
+
+/// Calculate the inclusion proof for the leaf at the index. The proof is the
+/// list of sibling hashes from the leaf up to the root.
+pub fn proof(h: Hashing, leaves: &[Bytes], index: usize) -> Option<Vec<Bytes>> {
+    let n = leaves.len();
+    if index >= n {
+        return None;
+    }
+    if n == 1 {
+        return Some(Vec::new());
+    }
+    let k = split_point(n);
+    let mut path;
+    if index < k {
+        path = proof(h, &leaves[..k], index)?;
+        path.push(root(h, &leaves[k..]));
+    } else {
+        path = proof(h, &leaves[k..], index - k)?;
+        path.push(root(h, &leaves[..k]));
+    }
+    Some(path)
+}
+
+/// Verify the inclusion proof for the leaf at the index in a tree of the
+/// given size. The proof is the concatenation of the sibling hashes. This is
+/// the verification algorithm from RFC 9162 section 2.1.3.2.
+pub fn verify(h: Hashing, leaf: &[u8], proof: &[u8], index: usize, size: usize, root: &[u8]) -> bool {
+    verify_with(&crypto::DEFAULT, h, leaf, proof, index, size, root)
+}
+
+pub(crate) fn verify_with(p: &dyn CryptoProvider, h: Hashing, leaf: &[u8], proof: &[u8], index: usize, size: usize, root: &[u8]) -> bool {
+    let path = proof.chunks_exact(h.size());
+    if index >= size || !path.remainder().is_empty() {
+        return false;
+    }
+
+    let mut fnode = index;
+    let mut snode = size - 1;
+    let mut r = leaf_hash_with(p, h, leaf);
+    for sibling in path {
+        if snode == 0 {
+            return false;
+        }
+        if fnode & 1 == 1 || fnode == snode {
+            r = node_hash_with(p, h, sibling, &r);
+            if fnode & 1 == 0 {
+                while fnode & 1 == 0 && fnode != 0 {
+                    fnode >>= 1;
+                    snode >>= 1;
+                }
+            }
+        } else {
+            r = node_hash_with(p, h, &r, sibling);
+        }
+        fnode >>= 1;
+        snode >>= 1;
+    }
+    snode == 0 && r.as_ref() == root
+}
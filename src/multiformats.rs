@@ -0,0 +1,153 @@
+use bytes::{
+    BufMut,
+    Bytes,
+    BytesMut
+};
+use crate::{
+    crypto::{
+        self,
+        CryptoProvider
+    },
+    der::Key,
+    Hashing,
+    Signing
+};
+
+// Multicodec keys and multihash digests from https://github.com/multiformats.
+// Both start with an unsigned LEB128 varint code from the multicodec table:
+//
+//   multicodec key = code || key
+//   multihash      = code || digest length || digest
+//
+// did:key identifiers are "did:key:" followed by a multibase encoded
+// multicodec public key.
+
+const ED25519_PUB: u64 = 0xed;
+const ED25519_PRIV: u64 = 0x1300;
+const SECP256K1_PUB: u64 = 0xe7;
+const SECP256K1_PRIV: u64 = 0x1301;
+const SHA2_256: u64 = 0x12;
+const SHA2_512: u64 = 0x13;
+const BLAKE3: u64 = 0x1e;
+
+// the multiformats spec limits varints to 9 bytes (63 bits)
+const MAX_VARINT_LEN: usize = 9;
+
+pub(crate) fn put_varint(buf: &mut BytesMut, v: u64) {
+    let mut v = v;
+    while v >= 0x80 {
+        buf.put_u8((v as u8) | 0x80);
+        v >>= 7;
+    }
+    buf.put_u8(v as u8);
+}
+
+// read a varint from the front of the buffer, returning it and whatever
+// follows it
+pub(crate) fn varint(buf: &[u8]) -> Result<(u64, &[u8]), &'static str> {
+    let mut v = 0u64;
+    for (i, b) in buf.iter().enumerate().take(MAX_VARINT_LEN) {
+        v |= ((b & 0x7f) as u64) << (7 * i);
+        if b & 0x80 == 0 {
+            // varints must be minimally encoded
+            if i > 0 && *b == 0 {
+                return Err("varint is not minimally encoded");
+            }
+            return Ok((v, &buf[i + 1..]));
+        }
+    }
+    Err("invalid varint")
+}
+
+/// Parse a multicodec prefixed key
+pub fn unwrap_key(b: &[u8]) -> Result<Key, &'static str> {
+    unwrap_key_with(&crypto::DEFAULT, b)
+}
+
+// parse the key, deriving the keypair of a private key with the provider
+pub(crate) fn unwrap_key_with(p: &dyn CryptoProvider, b: &[u8]) -> Result<Key, &'static str> {
+    let (code, key) = varint(b)?;
+    let id = match code {
+        ED25519_PUB | ED25519_PRIV => Signing::Ed25519,
+        SECP256K1_PUB | SECP256K1_PRIV => Signing::Secp256k1,
+        _ => return Err("unsupported multicodec key")
+    };
+    if id.supported().is_err() {
+        return Err("unsupported multicodec key");
+    }
+    match code {
+        ED25519_PUB => {
+            if key.len() != 32 {
+                return Err("Ed25519 public key must be 32 bytes");
+            }
+            Ok(Key::Public(Signing::Ed25519, key.to_vec()))
+        },
+        ED25519_PRIV => {
+            let (_, sk) = p.keypair_from_seed(Signing::Ed25519, key)
+                .map_err(|_| "Ed25519 private key must be 32 bytes")?;
+            Ok(Key::Private(Signing::Ed25519, sk))
+        },
+        SECP256K1_PUB => {
+            if key.len() != 33 {
+                return Err("Secp256k1 public key must be 33 bytes");
+            }
+            Ok(Key::Public(Signing::Secp256k1, key.to_vec()))
+        },
+        _ => {
+            let (_, sk) = p.keypair_from_seed(Signing::Secp256k1, key)
+                .map_err(|_| "Secp256k1 private key must be 32 bytes")?;
+            Ok(Key::Private(Signing::Secp256k1, sk))
+        }
+    }
+}
+
+fn hashing_code(h: Hashing) -> u64 {
+    match h {
+        Hashing::SHA256 => SHA2_256,
+        Hashing::SHA512 => SHA2_512,
+        Hashing::BLAKE3 => BLAKE3
+    }
+}
+
+/// Wrap a digest as a multihash
+pub fn wrap_multihash(h: Hashing, digest: &[u8]) -> Result<Bytes, &'static str> {
+    if digest.len() != h.size() {
+        return Err("digest is the wrong size for the hashing algorithm");
+    }
+    let mut buf = BytesMut::with_capacity(digest.len() + 4);
+    put_varint(&mut buf, hashing_code(h));
+    put_varint(&mut buf, digest.len() as u64);
+    buf.put_slice(digest);
+    Ok(buf.freeze())
+}
+
+/// The shortest truncated digest that 'MULTIHASHVERIFY' accepts. A shorter one
+/// could be matched by trying a few random inputs.
+pub const MIN_VERIFY_DIGEST: usize = 16;
+
+/// Parse a multihash into the hashing algorithm and the digest. The digest may
+/// be truncated but never longer than the algorithm's output.
+pub fn unwrap_multihash(mh: &[u8]) -> Result<(Hashing, &[u8]), &'static str> {
+    let (code, rest) = varint(mh)?;
+    let h = match code {
+        SHA2_256 => Hashing::SHA256,
+        SHA2_512 => Hashing::SHA512,
+        BLAKE3 => Hashing::BLAKE3,
+        _ => return Err("unsupported multihash algorithm")
+    };
+    if h.supported().is_err() {
+        return Err("unsupported multihash algorithm");
+    }
+    let (len, digest) = varint(rest)?;
+    if digest.is_empty() {
+        // an empty digest would match any data
+        return Err("multihash digest is empty");
+    }
+    if len as usize != digest.len() {
+        return Err("multihash length does not match the digest");
+    }
+    if digest.len() > h.size() {
+        return Err("multihash digest is longer than the hashing algorithm's output");
+    }
+    Ok((h, digest))
+}
@@ -0,0 +1,75 @@
+use crate::{
+    AppIO,
+    CCLang,
+    Context,
+    Machine
+};
+use std::panic::{
+    self,
+    AssertUnwindSafe
+};
+
+// A script succeeds when it ends with TRUE on top of the stack. Anything else
+// is a failure, including a script that stops early with 'ASSERT' or 'HALT'.
+// A script that stops with an 'Error' on top is reported as an error so that
+// callers can tell a bad signature from a broken script. Most opcodes still
+// panic when they are given the wrong types, so a panic while running is
+// caught and reported as an error too, after the panic hook has run.
+//
+// With the clean stack rule a script only succeeds when TRUE is the only item
+// left on the stack. Without it a script can leave values under the result
+// that the verifier never looks at.
+
+/// The result of running a script
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScriptOutcome {
+    Success,
+    Failure,
+    Error(String)
+}
+
+impl ScriptOutcome {
+    /// True if the script succeeded
+    pub fn is_success(&self) -> bool {
+        *self == ScriptOutcome::Success
+    }
+}
+
+/// Run the machine to the end and judge the result. If clean_stack is true the
+/// script must leave nothing but TRUE on the stack to succeed.
+pub fn run(machine: &mut Machine<CCLang>, io: &dyn AppIO<CCLang>, clean_stack: bool) -> ScriptOutcome {
+    run_with(machine, io, clean_stack, &Context::new())
+}
+
+/// Run the machine to the end with the context and judge the result, the same
+/// as run
+pub fn run_with(machine: &mut Machine<CCLang>, io: &dyn AppIO<CCLang>, clean_stack: bool, ctx: &Context) -> ScriptOutcome {
+    let result = match panic::catch_unwind(AssertUnwindSafe(|| ctx.execute(machine, io))) {
+        Ok(result) => result,
+        Err(e) => {
+            let msg = match e.downcast_ref::<&str>() {
+                Some(s) => s.to_string(),
+                None => e.downcast_ref::<String>().cloned().unwrap_or_default()
+            };
+            return ScriptOutcome::Error(format!("the script panicked: {}", msg));
+        }
+    };
+    match result {
+        Some(mut result) => {
+            let top = result.pop();
+            if clean_stack && result.size() > 0 {
+                return ScriptOutcome::Failure;
+            }
+            match top {
+                Some(CCLang::Boolean(true)) => ScriptOutcome::Success,
+                _ => ScriptOutcome::Failure
+            }
+        },
+        None => {
+            match machine.pop() {
+                Some(CCLang::Error(e)) => ScriptOutcome::Error(e),
+                _ => ScriptOutcome::Failure
+            }
+        }
+    }
+}
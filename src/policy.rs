@@ -0,0 +1,118 @@
+use crate::{
+    CCLang,
+    Encoding,
+    Encryption,
+    Hashing,
+    Signing
+};
+
+// An execution policy limits what a script may do, on top of what is built
+// in. A host sets it in the execution context so the machine refuses the
+// instructions and algorithms it doesn't allow, and passes it to
+// 'check::check_with' to refuse the script before running it at all.
+
+/// The opcodes and algorithms a script is allowed to use
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExecutionPolicy {
+    opcodes: Option<Vec<CCLang>>,
+    encodings: Option<Vec<Encoding>>,
+    encryption: Option<Vec<Encryption>>,
+    signing: Option<Vec<Signing>>,
+    hashing: Option<Vec<Hashing>>,
+    denied: Vec<CCLang>
+}
+
+// an item is allowed when there is no allow-list or the list holds it
+fn listed<T: PartialEq>(list: &Option<Vec<T>>, x: &T) -> bool {
+    list.as_ref().is_none_or(|l| l.contains(x))
+}
+
+// start or extend an allow-list
+fn extend<T: Clone>(list: &mut Option<Vec<T>>, items: &[T]) {
+    list.get_or_insert_with(Vec::new).extend_from_slice(items);
+}
+
+impl ExecutionPolicy {
+    /// A policy that allows everything
+    pub fn new() -> Self {
+        ExecutionPolicy::default()
+    }
+
+    /// A policy for verifiers: no file I/O, no encryption or decryption, no
+    /// signing and no handling of secrets
+    pub fn verify_only() -> Self {
+        let mut policy = ExecutionPolicy::new();
+        policy.deny(&[
+            CCLang::Open,
+            CCLang::Read,
+            CCLang::Write,
+            CCLang::Seek,
+            CCLang::Close,
+            CCLang::Encrypt,
+            CCLang::Decrypt,
+            CCLang::Sign,
+            CCLang::ToSecret,
+            CCLang::Reveal,
+            CCLang::Split,
+            CCLang::Combine
+        ]);
+        policy
+    }
+
+    /// Only allow the given opcodes, along with any allowed before
+    pub fn allow_opcodes(&mut self, opcodes: &[CCLang]) -> &mut Self {
+        extend(&mut self.opcodes, opcodes);
+        self
+    }
+
+    /// Only allow the given encodings, along with any allowed before
+    pub fn allow_encodings(&mut self, encodings: &[Encoding]) -> &mut Self {
+        extend(&mut self.encodings, encodings);
+        self
+    }
+
+    /// Only allow the given encryption algorithms, along with any allowed
+    /// before
+    pub fn allow_encryption(&mut self, encryption: &[Encryption]) -> &mut Self {
+        extend(&mut self.encryption, encryption);
+        self
+    }
+
+    /// Only allow the given signing algorithms, along with any allowed before
+    pub fn allow_signing(&mut self, signing: &[Signing]) -> &mut Self {
+        extend(&mut self.signing, signing);
+        self
+    }
+
+    /// Only allow the given hashing algorithms, along with any allowed before
+    pub fn allow_hashing(&mut self, hashing: &[Hashing]) -> &mut Self {
+        extend(&mut self.hashing, hashing);
+        self
+    }
+
+    /// Refuse the given opcodes and algorithm identifiers, even if they are
+    /// allowed
+    pub fn deny(&mut self, items: &[CCLang]) -> &mut Self {
+        self.denied.extend_from_slice(items);
+        self
+    }
+
+    /// Whether the policy allows the instruction. Data is always allowed
+    /// except for the algorithm identifiers, which are checked inside lists
+    /// too. Multibase text is also checked against the encoding its prefix
+    /// names when it is decoded at run time.
+    pub fn allows(&self, i: &CCLang) -> bool {
+        if self.denied.contains(i) {
+            return false;
+        }
+        match i {
+            CCLang::EncodingId(e) => listed(&self.encodings, e),
+            CCLang::EncryptionId(e) => listed(&self.encryption, e),
+            CCLang::SigningId(s) => listed(&self.signing, s),
+            CCLang::HashingId(h) => listed(&self.hashing, h),
+            CCLang::List(l) => l.iter().all(|i| self.allows(i)),
+            _ if i.type_name() == "Opcode" => listed(&self.opcodes, i),
+            _ => true
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::{
+    clone::Clone,
+    cmp::PartialEq,
+    convert::From,
+    fmt
+};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+
+// compare in constant time
+pub(crate) fn memcmp(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && bool::from(a.ct_eq(b))
+}
+
+// wipe the bytes in a way that isn't optimized away
+pub(crate) fn memzero(b: &mut [u8]) {
+    b.zeroize();
+}
+
+/// Secret key material. The bytes are wiped when the Secret is dropped, they
+/// are never printed by Debug, there is no Display so they can't be written
+/// into a script and they are compared in constant time.
+pub struct Secret(Vec<u8>);
+
+impl Secret {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<Vec<u8>> for Secret {
+    fn from(v: Vec<u8>) -> Self {
+        Secret(v)
+    }
+}
+
+impl From<&[u8]> for Secret {
+    fn from(s: &[u8]) -> Self {
+        Secret(s.to_vec())
+    }
+}
+
+impl AsRef<[u8]> for Secret {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl Clone for Secret {
+    fn clone(&self) -> Self {
+        Secret(self.0.clone())
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        memzero(&mut self.0);
+    }
+}
+
+impl PartialEq for Secret {
+    fn eq(&self, other: &Self) -> bool {
+        memcmp(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Secret(REDACTED)")
+    }
+}
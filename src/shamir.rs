@@ -0,0 +1,128 @@
+use bytes::{
+    BufMut,
+    Bytes,
+    BytesMut
+};
+use crate::{
+    crypto::{
+        self,
+        CryptoProvider
+    },
+    secret::memzero,
+    Secret
+};
+
+// Shamir secret sharing over GF(256) using the AES reduction polynomial
+// x^8 + x^4 + x^3 + x + 1. Each share is encoded as a single byte holding the
+// share index (the x coordinate, 1..=255) followed by one y coordinate byte
+// for every byte of the secret.
+
+// multiply in constant time: always eight rounds, with masks instead of
+// branches on the bits of the secret bytes
+fn gf_mul(a: u8, b: u8) -> u8 {
+    let mut a = a;
+    let mut b = b;
+    let mut p = 0u8;
+    for _ in 0..8 {
+        p ^= a & (b & 1).wrapping_neg();
+        let carry = (a >> 7).wrapping_neg();
+        a = (a << 1) ^ (0x1b & carry);
+        b >>= 1;
+    }
+    p
+}
+
+fn gf_inv(a: u8) -> u8 {
+    // a^254 is the multiplicative inverse of a in GF(256)
+    let mut r = 1u8;
+    let mut x = a;
+    let mut e = 254u8;
+    while e != 0 {
+        if e & 1 != 0 {
+            r = gf_mul(r, x);
+        }
+        x = gf_mul(x, x);
+        e >>= 1;
+    }
+    r
+}
+
+/// Split the secret into n shares, any k of which can recombine the secret.
+pub fn split(secret: &[u8], k: usize, n: usize) -> Result<Vec<Bytes>, &'static str> {
+    split_with(&crypto::DEFAULT, secret, k, n)
+}
+
+/// Split the secret using the random numbers from the crypto provider
+pub fn split_with(p: &dyn CryptoProvider, secret: &[u8], k: usize, n: usize) -> Result<Vec<Bytes>, &'static str> {
+    if k == 0 || k > n {
+        return Err("threshold must be between 1 and the number of shares");
+    }
+    if n > 255 {
+        return Err("at most 255 shares are supported");
+    }
+
+    // one random polynomial of degree k - 1 per secret byte with the secret
+    // byte as the constant term
+    let mut coeffs = vec![0u8; secret.len() * (k - 1)];
+    p.random(&mut coeffs);
+
+    let mut shares = Vec::with_capacity(n);
+    for x in 1..=n {
+        let x = x as u8;
+        let mut share = BytesMut::with_capacity(secret.len() + 1);
+        share.put_u8(x);
+        for (i, s) in secret.iter().enumerate() {
+            // evaluate the polynomial at x using Horner's method
+            let poly = &coeffs[i * (k - 1)..(i + 1) * (k - 1)];
+            let y = poly.iter().rev().fold(0u8, |acc, c| gf_mul(acc, x) ^ c);
+            share.put_u8(gf_mul(y, x) ^ s);
+        }
+        shares.push(share.freeze());
+    }
+
+    // wipe the random coefficients
+    memzero(&mut coeffs);
+    Ok(shares)
+}
+
+/// Recombine the secret from the given shares using Lagrange interpolation
+/// at x = 0.
+pub fn combine(shares: &[Bytes]) -> Result<Secret, &'static str> {
+    if shares.is_empty() {
+        return Err("no shares to combine");
+    }
+    let len = shares[0].len();
+    if len == 0 {
+        return Err("share is missing its index");
+    }
+    for (i, s) in shares.iter().enumerate() {
+        if s.len() != len {
+            return Err("shares have different lengths");
+        }
+        if s[0] == 0 {
+            return Err("share index must not be zero");
+        }
+        if shares[..i].iter().any(|p| p[0] == s[0]) {
+            return Err("duplicate share index");
+        }
+    }
+
+    let mut secret = Vec::with_capacity(len - 1);
+    for b in 1..len {
+        let mut acc = 0u8;
+        for (i, si) in shares.iter().enumerate() {
+            // lagrange basis polynomial for share i evaluated at 0
+            let mut num = 1u8;
+            let mut den = 1u8;
+            for (j, sj) in shares.iter().enumerate() {
+                if i != j {
+                    num = gf_mul(num, sj[0]);
+                    den = gf_mul(den, si[0] ^ sj[0]);
+                }
+            }
+            acc ^= gf_mul(si[b], gf_mul(num, gf_inv(den)));
+        }
+        secret.push(acc);
+    }
+    Ok(Secret::from(secret))
+}
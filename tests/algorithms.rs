@@ -0,0 +1,249 @@
+#[cfg(not(feature = "hash-blake3"))]
+mod unsupported_hashing {
+    use bytes::Bytes;
+    use cclang::{
+        check::check,
+        CCLang::{
+            self,
+            Binary,
+            Error,
+            GetVar,
+            Hash,
+            HashingId,
+            Text
+        },
+        Context,
+        Hashing,
+        Machine,
+        NullIO,
+        Script,
+        UnsupportedAlgorithm
+    };
+
+    #[test]
+    pub fn rejected() {
+        let e = Hashing::BLAKE3.supported().unwrap_err();
+        assert_eq!(e, UnsupportedAlgorithm { algorithm: "BLAKE3".to_string(), feature: "hash-blake3" });
+        let msg = "BLAKE3 is not supported, it needs the hash-blake3 feature";
+        assert_eq!(e.to_string(), msg);
+
+        // the token is recognized but rejected when parsing
+        let parsed = serde_json::from_str::<Script<CCLang>>(r#""616263 Hex DECODE BLAKE3 HASH""#);
+        assert!(parsed.unwrap_err().to_string().contains(msg));
+
+        // and when checking or running a script built in code
+        let script = Script::from(vec![HashingId(Hashing::BLAKE3)]);
+        assert_eq!(check(&script), Err(format!("{} at 0", msg)));
+        let mut machine = Machine::from(script);
+        assert!(machine.execute(&NullIO).is_none());
+        assert_eq!(machine.pop(), Some(Error(msg.to_string())));
+
+        // and when it reaches an opcode without being pushed as a literal
+        let script = Script::from(vec![Binary(Bytes::from_static(b"abc")), Text("h".to_string()), GetVar, Hash]);
+        let mut ctx = Context::new();
+        ctx.bind("h", HashingId(Hashing::BLAKE3));
+        let mut machine = Machine::from(script);
+        assert!(ctx.execute(&mut machine, &NullIO).is_none());
+        assert_eq!(machine.pop(), Some(Error(msg.to_string())));
+    }
+}
+
+#[cfg(not(feature = "sign-secp256k1"))]
+mod unsupported_signing {
+    use cclang::{
+        check::check,
+        CCLang::{
+            Index,
+            SigningId
+        },
+        Script,
+        Signing
+    };
+
+    #[test]
+    pub fn rejected() {
+        let msg = "Secp256k1 is not supported, it needs the sign-secp256k1 feature";
+        assert!(serde_json::from_str::<Signing>(r#""Secp256k1""#).unwrap_err().to_string().contains(msg));
+        let script = Script::from(vec![Index(1), SigningId(Signing::Secp256k1)]);
+        assert_eq!(check(&script), Err(format!("{} at 1", msg)));
+    }
+}
+
+#[cfg(not(feature = "encoding-base32"))]
+mod unsupported_encoding {
+    use cclang::Encoding;
+
+    #[test]
+    pub fn rejected() {
+        let msg = "Base32 is not supported, it needs the encoding-base32 feature";
+        assert_eq!(Encoding::Base32.decode("MFRGG==="), Err(msg.to_string()));
+        // the base32 multibase prefixes need the feature as well
+        assert_eq!(Encoding::Multibase.decode("bmfrgg"), Err(msg.to_string()));
+        assert_eq!(Encoding::Multibase.decode("mYWJj"), Ok(b"abc".to_vec()));
+    }
+}
+
+#[cfg(feature = "hash-blake3")]
+mod blake3 {
+    use bytes::Bytes;
+    use cclang::{
+        multiformats,
+        CCLang::{
+            self,
+            Binary,
+            Hash,
+            HashingId
+        },
+        Hashing,
+        Machine,
+        NullIO,
+        Script
+    };
+
+    const ABC: &str = "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85";
+
+    #[test]
+    pub fn hash() {
+        let script = Script::from(vec![Binary(Bytes::from_static(b"abc")), HashingId(Hashing::BLAKE3), Hash]);
+        let mut machine = Machine::from(script);
+        let mut result = machine.execute(&NullIO).unwrap();
+        assert_eq!(result.pop(), Some(Binary(Bytes::from(hex::decode(ABC).unwrap()))));
+    }
+
+    #[test]
+    pub fn multihash() {
+        let digest = hex::decode(ABC).unwrap();
+        let mh = multiformats::wrap_multihash(Hashing::BLAKE3, &digest).unwrap();
+        assert_eq!(&mh[..2], &[0x1e, 0x20]);
+        assert_eq!(multiformats::unwrap_multihash(&mh).unwrap(), (Hashing::BLAKE3, digest.as_slice()));
+    }
+
+    #[test]
+    pub fn blake3_ser_0() {
+        let s = serde_json::to_string(&Script::from(vec![HashingId(Hashing::BLAKE3)])).unwrap();
+        assert_eq!(s, r#""BLAKE3""#);
+    }
+
+    #[test]
+    pub fn blake3_de_0() {
+        let s: Script<CCLang> = serde_json::from_str(r#""blake3""#).unwrap();
+        assert_eq!(s, Script::from(vec![HashingId(Hashing::BLAKE3)]));
+    }
+}
+
+#[cfg(feature = "sign-secp256k1")]
+mod secp256k1 {
+    use bytes::Bytes;
+    use cclang::{
+        multiformats,
+        outcome::run,
+        der::Key,
+        CCLang::{
+            self,
+            Binary,
+            Secret,
+            Sign,
+            SigningId,
+            Verify
+        },
+        CryptoProvider,
+        Machine,
+        NullIO,
+        Script,
+        ScriptOutcome,
+        Signing
+    };
+    #[cfg(feature = "hash-sha2")]
+    use cclang::{
+        Envelope,
+        Hashing
+    };
+
+    fn provider() -> Box<dyn CryptoProvider> {
+        #[cfg(feature = "backend-sodium")]
+        return Box::new(cclang::Sodium);
+        #[cfg(not(feature = "backend-sodium"))]
+        return Box::new(cclang::RustCrypto);
+    }
+
+    fn keys(seed: u8) -> (Bytes, cclang::Secret) {
+        provider().keypair_from_seed(Signing::Secp256k1, &[seed; 32]).unwrap()
+    }
+
+    #[test]
+    pub fn sign_verify() {
+        let (pk, sk) = keys(1);
+        assert_eq!(pk.len(), 33 as usize);
+        let msg = Binary(Bytes::from_static(b"commit"));
+
+        // sign in one script and verify the signature in another
+        let mut machine = Machine::from(Script::from(vec![msg.clone(), Secret(sk), SigningId(Signing::Secp256k1), Sign]));
+        let sig = match machine.execute(&NullIO).unwrap().pop() {
+            Some(Binary(sig)) => sig,
+            _ => panic!()
+        };
+        assert_eq!(sig.len(), 64 as usize);
+        let verify = |pk: &Bytes| {
+            let mut machine = Machine::from(Script::from(vec![
+                Binary(sig.clone()),
+                Binary(pk.clone()),
+                msg.clone(),
+                SigningId(Signing::Secp256k1),
+                Verify
+            ]));
+            run(&mut machine, &NullIO, true)
+        };
+        assert_eq!(verify(&pk), ScriptOutcome::Success);
+        assert_eq!(verify(&keys(2).0), ScriptOutcome::Failure);
+    }
+
+    #[cfg(feature = "backend-rustcrypto")]
+    #[test]
+    pub fn providers_agree() {
+        let (pk, sk) = keys(3);
+        let sig = provider().sign(Signing::Secp256k1, sk.as_ref(), b"msg").unwrap();
+        let (pk2, sk2) = cclang::RustCrypto.keypair_from_seed(Signing::Secp256k1, &[3; 32]).unwrap();
+        assert_eq!((pk.clone(), sk.clone()), (pk2, sk2));
+        assert_eq!(cclang::RustCrypto.sign(Signing::Secp256k1, sk.as_ref(), b"msg").unwrap(), sig);
+        assert_eq!(cclang::RustCrypto.verify(Signing::Secp256k1, &pk, b"msg", &sig), Ok(true));
+    }
+
+    #[test]
+    pub fn multicodec_key() {
+        let (pk, sk) = keys(4);
+        let mut b = vec![0xe7, 0x01];
+        b.extend_from_slice(&pk);
+        match multiformats::unwrap_key(&b) {
+            Ok(Key::Public(Signing::Secp256k1, k)) => assert_eq!(k, pk.to_vec()),
+            _ => panic!()
+        }
+        let mut b = vec![0x81, 0x26];
+        b.extend_from_slice(sk.as_ref());
+        match multiformats::unwrap_key(&b) {
+            Ok(Key::Private(Signing::Secp256k1, k)) => assert_eq!(k, sk),
+            _ => panic!()
+        }
+    }
+
+    #[cfg(feature = "hash-sha2")]
+    #[test]
+    pub fn envelope() {
+        let policy = Script::from(vec![CCLang::Boolean(true)]);
+        let mut envelope = Envelope::new(&policy, Hashing::SHA256).unwrap();
+        envelope.sign(Signing::Secp256k1, &keys(5).1).unwrap();
+        assert_eq!(envelope.signatures()[0].public_key, keys(5).0);
+        assert_eq!(envelope.verify(&[keys(5).0], 1), Ok(policy));
+    }
+
+    #[test]
+    pub fn secp256k1_ser_0() {
+        let s = serde_json::to_string(&Script::from(vec![SigningId(Signing::Secp256k1)])).unwrap();
+        assert_eq!(s, r#""Secp256k1""#);
+    }
+
+    #[test]
+    pub fn secp256k1_de_0() {
+        let s: Script<CCLang> = serde_json::from_str(r#""secp256k1""#).unwrap();
+        assert_eq!(s, Script::from(vec![SigningId(Signing::Secp256k1)]));
+    }
+}
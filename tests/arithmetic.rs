@@ -0,0 +1,174 @@
+use cclang::{
+    CCLang::{
+        self,
+        Abs,
+        Add,
+        Boolean,
+        Div,
+        Error,
+        GreaterThanEqual,
+        Index,
+        Max,
+        Min,
+        Mod,
+        Mul,
+        Sub
+    },
+    Machine,
+    NullIO,
+    Script
+};
+
+fn run(script: Vec<CCLang>) -> CCLang {
+    let mut machine = Machine::from(Script::from(script));
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+    result.pop().unwrap()
+}
+
+fn run_error(script: Vec<CCLang>) -> String {
+    let mut machine = Machine::from(Script::from(script));
+
+    // the script stops at the failing opcode and leaves the error on the stack
+    assert!(machine.execute(&NullIO).is_none());
+    match machine.pop() {
+        Some(Error(e)) => e,
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn add() {
+    assert_eq!(run(vec![Index(2), Index(3), Add]), Index(5));
+    assert_eq!(run(vec![Index(-2), Index(3), Add]), Index(1));
+}
+
+#[test]
+pub fn sub() {
+    // the top of the stack is subtracted from the item below it
+    assert_eq!(run(vec![Index(2), Index(3), Sub]), Index(-1));
+    assert_eq!(run(vec![Index(7), Index(3), Sub]), Index(4));
+}
+
+#[test]
+pub fn mul() {
+    assert_eq!(run(vec![Index(6), Index(7), Mul]), Index(42));
+    assert_eq!(run(vec![Index(-6), Index(7), Mul]), Index(-42));
+}
+
+#[test]
+pub fn div() {
+    // integer division rounds toward zero
+    assert_eq!(run(vec![Index(7), Index(2), Div]), Index(3));
+    assert_eq!(run(vec![Index(-7), Index(2), Div]), Index(-3));
+}
+
+#[test]
+pub fn modulo() {
+    // the remainder takes the sign of the dividend
+    assert_eq!(run(vec![Index(7), Index(3), Mod]), Index(1));
+    assert_eq!(run(vec![Index(-7), Index(3), Mod]), Index(-1));
+}
+
+#[test]
+pub fn min_max() {
+    assert_eq!(run(vec![Index(7), Index(3), Min]), Index(3));
+    assert_eq!(run(vec![Index(7), Index(3), Max]), Index(7));
+}
+
+#[test]
+pub fn abs() {
+    assert_eq!(run(vec![Index(-7), Abs]), Index(7));
+    assert_eq!(run(vec![Index(7), Abs]), Index(7));
+}
+
+#[test]
+pub fn boolean_operands() {
+    // booleans count as 1 and 0
+    assert_eq!(run(vec![Boolean(true), Boolean(true), Add]), Index(2));
+    assert_eq!(run(vec![Boolean(false), Index(5), Add]), Index(5));
+}
+
+#[test]
+pub fn m_of_n() {
+    // 2 of 3 signature results are valid
+    let result = run(vec![
+        Boolean(true),
+        Boolean(false),
+        Add,
+        Boolean(true),
+        Add,
+        Index(2),
+        GreaterThanEqual
+    ]);
+    match result {
+        Boolean(b) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn overflow() {
+    assert_eq!(run_error(vec![Index(isize::MAX), Index(1), Add]), "integer overflow");
+    assert_eq!(run_error(vec![Index(isize::MIN), Index(1), Sub]), "integer overflow");
+    assert_eq!(run_error(vec![Index(isize::MAX), Index(2), Mul]), "integer overflow");
+    assert_eq!(run_error(vec![Index(isize::MIN), Index(-1), Div]), "integer overflow");
+    assert_eq!(run_error(vec![Index(isize::MIN), Abs]), "integer overflow");
+}
+
+#[test]
+pub fn division_by_zero() {
+    assert_eq!(run_error(vec![Index(1), Index(0), Div]), "division by zero");
+    assert_eq!(run_error(vec![Index(1), Index(0), Mod]), "division by zero");
+}
+
+#[test]
+pub fn arithmetic_ser_0() {
+    let script = Script::from(vec![
+        Index(1),
+        Index(2),
+        Add,
+        Index(3),
+        Sub,
+        Index(4),
+        Mul,
+        Index(5),
+        Div,
+        Index(6),
+        Mod,
+        Index(7),
+        Min,
+        Index(8),
+        Max,
+        Abs
+    ]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""1 2 ADD 3 SUB 4 MUL 5 DIV 6 MOD 7 MIN 8 MAX ABS""#);
+}
+
+#[test]
+pub fn arithmetic_de_0() {
+    let s1 = Script::from(vec![
+        Index(1),
+        Index(2),
+        Add,
+        Index(3),
+        Sub,
+        Index(4),
+        Mul,
+        Index(5),
+        Div,
+        Index(6),
+        Mod,
+        Index(7),
+        Min,
+        Index(8),
+        Max,
+        Abs
+    ]);
+    let s = r#""1 2 ADD 3 SUB 4 MUL 5 DIV 6 MOD 7 MIN 8 MAX ABS""#;
+    let s2: Script<CCLang> = serde_json::from_str(s).unwrap();
+    assert_eq!(s1, s2);
+}
@@ -0,0 +1,207 @@
+use bytes::{
+    BufMut,
+    BytesMut
+};
+use cclang::{
+    CCLang::{
+        self,
+        Binary,
+        Boolean,
+        Decode,
+        Encode,
+        EncodingId,
+        Equal,
+        Error,
+        Text
+    },
+    Encoding,
+    Machine,
+    NullIO,
+    Script
+};
+
+/* TEST DATA (from BIP 173 and BIP 350):
+Bech32:
+ hrp: abcdef
+data: 00443214c74254b635cf84653a56d7c675be77df
+text: abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw
+
+Bech32m:
+ hrp: abcdef
+data: ffbbcdeb38bdab49ca307b9ac5a928398a418820
+text: abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx
+*/
+
+#[test]
+pub fn decoding_bech32() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("00443214c74254b635cf84653a56d7c675be77df".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // decode and push the human-readable part and the binary
+        Text("abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw".to_string()),
+        EncodingId(Encoding::Bech32),
+        Decode
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should be the human-readable part and the binary
+    assert_eq!(result.size(), 2 as usize);
+
+    // the binary is on top of the human-readable part
+    assert_eq!(result.pop(), Some(Binary(b.freeze())));
+    assert_eq!(result.pop(), Some(Text("abcdef".to_string())));
+}
+
+#[test]
+pub fn decoding_bech32m() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("ffbbcdeb38bdab49ca307b9ac5a928398a418820".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // decode and push the human-readable part and the binary
+        Text("abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx".to_string()),
+        EncodingId(Encoding::Bech32m),
+        Decode
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should be the human-readable part and the binary
+    assert_eq!(result.size(), 2 as usize);
+
+    // the binary is on top of the human-readable part
+    assert_eq!(result.pop(), Some(Binary(b.freeze())));
+    assert_eq!(result.pop(), Some(Text("abcdef".to_string())));
+}
+
+#[test]
+pub fn encoding_bech32() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("00443214c74254b635cf84653a56d7c675be77df".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected string
+        Text("abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw".to_string()),
+
+        // encode the human-readable part and binary and push the string
+        Text("abcdef".to_string()),
+        Binary(b.freeze()),
+        EncodingId(Encoding::Bech32),
+        Encode,
+
+        // pop the encoded string and the expected string and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn encoding_bech32m() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("ffbbcdeb38bdab49ca307b9ac5a928398a418820".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected string
+        Text("abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx".to_string()),
+
+        // encode the human-readable part and binary and push the string
+        Text("abcdef".to_string()),
+        Binary(b.freeze()),
+        EncodingId(Encoding::Bech32m),
+        Encode,
+
+        // pop the encoded string and the expected string and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn decoding_bech32_bad_checksum() {
+    let script = Script::from(vec![
+        // the last character of the checksum is wrong
+        Text("abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxx".to_string()),
+        EncodingId(Encoding::Bech32),
+        Decode,
+        Text("abcdef".to_string())
+    ]);
+    let mut machine = Machine::from(script);
+
+    // the script stops at the DECODE
+    assert!(machine.execute(&NullIO).is_none());
+
+    // and leaves the error on the stack
+    match machine.pop() {
+        Some(Error(e)) => assert!(e.starts_with("failed to decode Bech32 text")),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn decoding_bech32_wrong_variant() {
+    let script = Script::from(vec![
+        // a valid Bech32m string is not valid Bech32
+        Text("abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx".to_string()),
+        EncodingId(Encoding::Bech32),
+        Decode
+    ]);
+    let mut machine = Machine::from(script);
+    assert!(machine.execute(&NullIO).is_none());
+    match machine.pop() {
+        Some(Error(e)) => assert!(e.starts_with("failed to decode Bech32 text")),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn bech32_ser_0() {
+    let script = Script::from(vec![
+        Text("abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx".to_string()),
+        EncodingId(Encoding::Bech32m),
+        Decode,
+        EncodingId(Encoding::Bech32),
+        Encode
+    ]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx Bech32m DECODE Bech32 ENCODE""#);
+}
+
+#[test]
+pub fn bech32_de_0() {
+    let s1 = Script::from(vec![
+        Text("abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx".to_string()),
+        EncodingId(Encoding::Bech32m),
+        Decode,
+        EncodingId(Encoding::Bech32),
+        Encode
+    ]);
+    let s = r#""abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx Bech32m DECODE Bech32 ENCODE""#;
+    let s2: Script<CCLang> = serde_json::from_str(s).unwrap();
+    assert_eq!(s1, s2);
+}
@@ -42,6 +42,15 @@ msg: 3n1re8FXBfyWATGbb6ri76dBQd3SH3VtzSyqrwXeXjxziz69mEdSKAa8MLJ31v6XHeaSao3Dq41
  pk: jPCzTz1V1QBgR1JxyxWQKwiSkjvSxaQsoVQBNFke7YL
  sk: 4NvijY3mSxxHjAdrrGzh21ryNSVByzDuVwgV6ETqMhQtFs1zbmCYPa3PSiHSj12dtGiDFtPb39eAP4mYJVQ2C9Rr
 sig: 4Ht2qwApkdkkdvXJSrFpe5vT3HBGWG2Zr3ZgWoyJ4XhQmCWccyEJZdNoQjjktpPo9SYy8Y45gaFNwTKAwJ9tQZix
+
+pk in the other encodings:
+  Base64NoPad: CtuA0vxNdK25kFmllrohcG2toeKf2FWmZM6BX4jmsWk
+  Base64UrlNoPad: CtuA0vxNdK25kFmllrohcG2toeKf2FWmZM6BX4jmsWk
+  Base58Check: 5nLe28ipD1og73PvoYg4esZ184DDdvomcnmBDfB1D5wvUAyM2
+  Base32: BLNYBUX4JV2K3OMQLGSZNORBOBW23IPCT7MFLJTEZ2AV7CHGWFUQ====
+  Base32Hex: 1BDO1KNS9LQARECGB6IPDEH1E1MQR8F2JVC5B9J4PQ0LV276M5KG====
+  Base32Crockford: 1BDR1MQW9NTAVECGB6JSDEH1E1PTV8F2KZC5B9K4ST0NZ276P5MG
+  Multibase: zjPCzTz1V1QBgR1JxyxWQKwiSkjvSxaQsoVQBNFke7YL
 */
 
 #[test]
@@ -137,6 +146,7 @@ pub fn decoding_base64url() {
     }
 }
 
+#[cfg(feature = "encoding-base58")]
 #[test]
 pub fn decoding_base58bitcoin() {
     let mut b = BytesMut::new();
@@ -261,6 +271,7 @@ pub fn encoding_base64url() {
     }
 }
 
+#[cfg(feature = "encoding-base58")]
 #[test]
 pub fn encoding_base58bitcoin() {
     let mut b = BytesMut::new();
@@ -292,3 +303,603 @@ pub fn encoding_base58bitcoin() {
     }
 }
 
+#[test]
+pub fn decoding_base64nopad() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected binary
+        Binary(b.freeze()),
+
+        // decode and push the binary
+        Text("CtuA0vxNdK25kFmllrohcG2toeKf2FWmZM6BX4jmsWk".to_string()),
+        EncodingId(Encoding::Base64NoPad),
+        Decode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn decoding_base64urlnopad() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected binary
+        Binary(b.freeze()),
+
+        // decode and push the binary
+        Text("CtuA0vxNdK25kFmllrohcG2toeKf2FWmZM6BX4jmsWk".to_string()),
+        EncodingId(Encoding::Base64UrlNoPad),
+        Decode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "encoding-base58")]
+#[test]
+pub fn decoding_base58check() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected binary
+        Binary(b.freeze()),
+
+        // decode and push the binary
+        Text("5nLe28ipD1og73PvoYg4esZ184DDdvomcnmBDfB1D5wvUAyM2".to_string()),
+        EncodingId(Encoding::Base58Check),
+        Decode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "encoding-base32")]
+#[test]
+pub fn decoding_base32() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected binary
+        Binary(b.freeze()),
+
+        // decode and push the binary
+        Text("BLNYBUX4JV2K3OMQLGSZNORBOBW23IPCT7MFLJTEZ2AV7CHGWFUQ====".to_string()),
+        EncodingId(Encoding::Base32),
+        Decode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "encoding-base32")]
+#[test]
+pub fn decoding_base32hex() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected binary
+        Binary(b.freeze()),
+
+        // decode and push the binary
+        Text("1BDO1KNS9LQARECGB6IPDEH1E1MQR8F2JVC5B9J4PQ0LV276M5KG====".to_string()),
+        EncodingId(Encoding::Base32Hex),
+        Decode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "encoding-base32")]
+#[test]
+pub fn decoding_base32crockford() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected binary
+        Binary(b.freeze()),
+
+        // decode and push the binary
+        Text("1BDR1MQW9NTAVECGB6JSDEH1E1PTV8F2KZC5B9K4ST0NZ276P5MG".to_string()),
+        EncodingId(Encoding::Base32Crockford),
+        Decode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "encoding-base58")]
+#[test]
+pub fn decoding_multibase() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected binary
+        Binary(b.freeze()),
+
+        // decode and push the binary
+        Text("zjPCzTz1V1QBgR1JxyxWQKwiSkjvSxaQsoVQBNFke7YL".to_string()),
+        EncodingId(Encoding::Multibase),
+        Decode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "encoding-base32")]
+#[test]
+pub fn decoding_base32crockford_lenient() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected binary
+        Binary(b.freeze()),
+
+        // decode lower case text with hyphens and an O for a 0
+        Text("1bdr1mqw-9ntavecgb6jsdeh1e1ptv8f2kzc5b9k4sto-nz276p5mg".to_string()),
+        EncodingId(Encoding::Base32Crockford),
+        Decode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "encoding-base32")]
+#[test]
+pub fn decoding_multibase_base32() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected binary
+        Binary(b.freeze()),
+
+        // decode the multibase base32 text
+        Text("bblnybux4jv2k3omqlgsznorbobw23ipct7mfljtez2av7chgwfuq".to_string()),
+        EncodingId(Encoding::Multibase),
+        Decode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn decoding_multibase_hex() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected binary
+        Binary(b.freeze()),
+
+        // decode the multibase hex text
+        Text("f0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()),
+        EncodingId(Encoding::Multibase),
+        Decode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn decoding_multibase_base64url() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected binary
+        Binary(b.freeze()),
+
+        // decode the multibase base64url text
+        Text("uCtuA0vxNdK25kFmllrohcG2toeKf2FWmZM6BX4jmsWk".to_string()),
+        EncodingId(Encoding::Multibase),
+        Decode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn decoding_multibase_base64pad() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected binary
+        Binary(b.freeze()),
+
+        // decode the multibase padded base64 text
+        Text("MCtuA0vxNdK25kFmllrohcG2toeKf2FWmZM6BX4jmsWk=".to_string()),
+        EncodingId(Encoding::Multibase),
+        Decode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn encoding_base64nopad() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected string
+        Text("CtuA0vxNdK25kFmllrohcG2toeKf2FWmZM6BX4jmsWk".to_string()),
+
+        // encode and push the string
+        Binary(b.freeze()),
+        EncodingId(Encoding::Base64NoPad),
+        Encode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn encoding_base64urlnopad() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected string
+        Text("CtuA0vxNdK25kFmllrohcG2toeKf2FWmZM6BX4jmsWk".to_string()),
+
+        // encode and push the string
+        Binary(b.freeze()),
+        EncodingId(Encoding::Base64UrlNoPad),
+        Encode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "encoding-base58")]
+#[test]
+pub fn encoding_base58check() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected string
+        Text("5nLe28ipD1og73PvoYg4esZ184DDdvomcnmBDfB1D5wvUAyM2".to_string()),
+
+        // encode and push the string
+        Binary(b.freeze()),
+        EncodingId(Encoding::Base58Check),
+        Encode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "encoding-base32")]
+#[test]
+pub fn encoding_base32() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected string
+        Text("BLNYBUX4JV2K3OMQLGSZNORBOBW23IPCT7MFLJTEZ2AV7CHGWFUQ====".to_string()),
+
+        // encode and push the string
+        Binary(b.freeze()),
+        EncodingId(Encoding::Base32),
+        Encode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "encoding-base32")]
+#[test]
+pub fn encoding_base32hex() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected string
+        Text("1BDO1KNS9LQARECGB6IPDEH1E1MQR8F2JVC5B9J4PQ0LV276M5KG====".to_string()),
+
+        // encode and push the string
+        Binary(b.freeze()),
+        EncodingId(Encoding::Base32Hex),
+        Encode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "encoding-base32")]
+#[test]
+pub fn encoding_base32crockford() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected string
+        Text("1BDR1MQW9NTAVECGB6JSDEH1E1PTV8F2KZC5B9K4ST0NZ276P5MG".to_string()),
+
+        // encode and push the string
+        Binary(b.freeze()),
+        EncodingId(Encoding::Base32Crockford),
+        Encode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "encoding-base58")]
+#[test]
+pub fn encoding_multibase() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected string
+        Text("zjPCzTz1V1QBgR1JxyxWQKwiSkjvSxaQsoVQBNFke7YL".to_string()),
+
+        // encode and push the string
+        Binary(b.freeze()),
+        EncodingId(Encoding::Multibase),
+        Encode,
+       
+        // pop the decoded binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
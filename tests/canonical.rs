@@ -0,0 +1,231 @@
+use bytes::Bytes;
+use cclang::{
+    canonical::{
+        decode,
+        encode
+    },
+    CCLang::{
+        self,
+        Add,
+        Binary,
+        Boolean,
+        CallHash,
+        EncodingId,
+        HashingId,
+        Index,
+        List,
+        Secret,
+        Text
+    },
+    Encoding,
+    Hashing,
+    Script
+};
+#[cfg(feature = "hash-sha2")]
+use cclang::{
+    check::check,
+    outcome::{
+        run,
+        run_with
+    },
+    CCLang::{
+        Decode,
+        Equal,
+        Error,
+        GetVar
+    },
+    Context,
+    Machine,
+    NullIO,
+    ScriptId,
+    ScriptOutcome
+};
+
+// the policy committed to by hash
+#[cfg(feature = "hash-sha2")]
+fn policy() -> Script<CCLang> {
+    Script::from(vec![Index(2), Add, Index(5), Equal])
+}
+
+fn binary(b: &[u8]) -> CCLang {
+    Binary(Bytes::copy_from_slice(b))
+}
+
+#[test]
+pub fn canonical_form() {
+    let script = Script::from(vec![Boolean(true), Text("12".to_string()), Index(12), EncodingId(Encoding::Hex), Add]);
+    let b = encode(&script).unwrap();
+    assert_eq!(hex::encode(&b), concat!(
+        "010101",                   // TRUE
+        "02023132",                 // "12"
+        "0308000000000000000c",     // 12
+        "0403486578",               // Hex
+        "0003414444"                // ADD
+    ));
+    assert_eq!(decode(&b).unwrap(), script);
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn text_and_index_differ() {
+    let text = Script::from(vec![Text("12".to_string())]);
+    let index = Script::from(vec![Index(12)]);
+    assert_ne!(text.id(Hashing::SHA256).unwrap(), index.id(Hashing::SHA256).unwrap());
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn binary_as_written() {
+    // a Binary has the same id as the text form it is written as
+    let script = Script::from(vec![binary(&[0xab, 0xcd])]);
+    let s = serde_json::to_string(&script).unwrap();
+    let parsed: Script<CCLang> = serde_json::from_str(&s).unwrap();
+    assert_eq!(script.id(Hashing::SHA256).unwrap(), parsed.id(Hashing::SHA256).unwrap());
+    assert_eq!(script.id(Hashing::SHA512).unwrap().len(), 64 as usize);
+
+    // and so does a list
+    let list = Script::from(vec![List(vec![Index(1), Index(2)])]);
+    let packed: Script<CCLang> = serde_json::from_str(r#""1 2 2 PACK""#).unwrap();
+    assert_eq!(list.id(Hashing::SHA256).unwrap(), packed.id(Hashing::SHA256).unwrap());
+}
+
+#[test]
+pub fn not_canonical() {
+    // lowercase opcode token
+    assert!(decode(&hex::decode("0003616464").unwrap()).is_err());
+    // an opcode record holding a number
+    assert!(decode(&hex::decode("00023132").unwrap()).is_err());
+    // truncated
+    assert!(decode(&hex::decode("000541").unwrap()).is_err());
+    // no canonical form for secrets
+    assert!(encode(&Script::from(vec![Secret(cclang::Secret::from(vec![1u8]))])).is_err());
+    let list = List(vec![Index(1), Secret(cclang::Secret::from(vec![1u8]))]);
+    assert_eq!(encode(&Script::from(vec![list])), Err("a Secret has no canonical form".to_string()));
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn callhash() {
+    let s = encode(&policy()).unwrap();
+    let h = policy().id(Hashing::SHA256).unwrap();
+    let script = Script::from(vec![
+        Index(3),
+        Binary(s),
+        Binary(h),
+        HashingId(Hashing::SHA256),
+        CallHash
+    ]);
+    let mut machine = Machine::from(script.clone());
+    assert_eq!(run(&mut machine, &NullIO, true), ScriptOutcome::Success);
+
+    // the called script is checked with the script that calls it
+    assert_eq!(check(&script).unwrap().cost, 4 + 5);
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn callhash_depth() {
+    // the script that calls the script n levels down
+    let nested = |n: usize| {
+        let mut script = Script::from(vec![Boolean(true)]);
+        for _ in 0..n {
+            script = Script::from(vec![
+                Binary(encode(&script).unwrap()),
+                Binary(script.id(Hashing::SHA256).unwrap()),
+                HashingId(Hashing::SHA256),
+                CallHash
+            ]);
+        }
+        Machine::from(script)
+    };
+    let ctx = Context::new();
+    assert!(ctx.execute(&mut nested(8), &NullIO).is_some());
+    let mut machine = nested(9);
+    assert!(ctx.execute(&mut machine, &NullIO).is_none());
+    assert_eq!(machine.pop(), Some(Error("CALLHASH is nested too deeply".to_string())));
+
+    // a failed run leaves nothing behind in the context
+    assert!(ctx.execute(&mut nested(8), &NullIO).is_some());
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn callhash_mismatch() {
+    let s = encode(&Script::from(vec![Index(3), Add, Index(5), Equal])).unwrap();
+    let h = policy().id(Hashing::SHA256).unwrap();
+    let script = Script::from(vec![
+        Index(2),
+        Binary(s),
+        Binary(h),
+        HashingId(Hashing::SHA256),
+        CallHash
+    ]);
+    assert_eq!(check(&script), Err("CALLHASH at 4 does not match its hash".to_string()));
+    let mut machine = Machine::from(script);
+    assert!(machine.execute(&NullIO).is_none());
+    assert_eq!(machine.pop(), Some(Error("script does not match the hash".to_string())));
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn callhash_inputs() {
+    let called = Script::from(vec![Text("threshold".to_string()), GetVar, Equal]);
+    let script = Script::from(vec![
+        Index(2),
+        Binary(encode(&called).unwrap()),
+        Binary(called.id(Hashing::SHA256).unwrap()),
+        HashingId(Hashing::SHA256),
+        CallHash
+    ]);
+    assert_eq!(check(&script).unwrap().inputs, vec!["threshold".to_string()]);
+
+    let mut ctx = Context::new();
+    ctx.bind("threshold", Index(2));
+    let mut machine = Machine::from(script);
+    assert!(run_with(&mut machine, &NullIO, true, &ctx).is_success());
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn callhash_computed() {
+    let script = Script::from(vec![
+        Text("00".to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+        Index(0),
+        Add,
+        HashingId(Hashing::SHA256),
+        CallHash
+    ]);
+    assert_eq!(check(&script), Err("CALLHASH at 6 needs a literal script".to_string()));
+}
+
+#[test]
+pub fn callhash_ser_0() {
+    let script = Script::from(vec![
+        binary(&[0x03, 0x08, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]),
+        binary(&[0xc0, 0xff, 0xee]),
+        HashingId(Hashing::SHA256),
+        CallHash
+    ]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""0308ffffffffffffffff Hex DECODE c0ffee Hex DECODE SHA256 CALLHASH""#);
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn callhash_de_0() {
+    let s1 = Script::from(vec![
+        Text("0308ffffffffffffffff".to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+        Text("c0ffee".to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+        HashingId(Hashing::SHA256),
+        CallHash
+    ]);
+    let s = r#""0308ffffffffffffffff Hex DECODE c0ffee Hex DECODE SHA256 CALLHASH""#;
+    let s2: Script<CCLang> = serde_json::from_str(s).unwrap();
+    assert_eq!(s1, s2);
+}
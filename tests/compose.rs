@@ -0,0 +1,173 @@
+#[cfg(feature = "sign-ed25519")]
+use bytes::Bytes;
+use cclang::{
+    outcome::run,
+    CCLang::{
+        self,
+        Boolean,
+        Else,
+        Equal,
+        Fi,
+        If,
+        Index,
+        Return
+    },
+    Compose,
+    Machine,
+    NullIO,
+    Script,
+    ScriptOutcome
+};
+#[cfg(feature = "sign-ed25519")]
+use cclang::{
+    compose::endorsement_message,
+    CCLang::{
+        Binary,
+        SigningId,
+        Verify
+    },
+    Signing
+};
+#[cfg(feature = "sign-ed25519")]
+use sodiumoxide::crypto::sign::{
+    Seed,
+    keypair_from_seed,
+    sign_detached
+};
+
+fn outcome(script: Script<CCLang>) -> ScriptOutcome {
+    let mut machine = Machine::from(script);
+    run(&mut machine, &NullIO, true)
+}
+
+fn truth(b: bool) -> Script<CCLang> {
+    Script::from(vec![Boolean(b)])
+}
+
+// sign the message with the key made from the seed byte and return the
+// signature and public key
+#[cfg(feature = "sign-ed25519")]
+fn sign(seed: u8, msg: &[u8]) -> (Bytes, Bytes) {
+    let (pk, sk) = keypair_from_seed(&Seed([seed; 32]));
+    let sig = sign_detached(msg, &sk);
+    (Bytes::copy_from_slice(sig.as_ref()), Bytes::copy_from_slice(pk.as_ref()))
+}
+
+// the script that verifies a signature over the message
+#[cfg(feature = "sign-ed25519")]
+fn signature(sig: &Bytes, pk: &Bytes, msg: &[u8]) -> Script<CCLang> {
+    Script::from(vec![
+        Binary(sig.clone()),
+        Binary(pk.clone()),
+        Binary(Bytes::copy_from_slice(msg)),
+        SigningId(Signing::Ed25519),
+        Verify
+    ])
+}
+
+#[test]
+pub fn then() {
+    let a = Script::from(vec![Index(1)]);
+    let b = Script::from(vec![Index(1), Equal]);
+    let s = a.then(&b).unwrap();
+    assert_eq!(s, Script::from(vec![Index(1), Index(1), Equal]));
+    assert_eq!(outcome(s), ScriptOutcome::Success);
+}
+
+#[test]
+pub fn and() {
+    let s = truth(true).and(&truth(false)).unwrap();
+    assert_eq!(s, Script::from(vec![Boolean(true), If, Boolean(false), Else, Boolean(false), Fi]));
+    assert_eq!(outcome(truth(true).and(&truth(true)).unwrap()), ScriptOutcome::Success);
+    assert_eq!(outcome(truth(true).and(&truth(false)).unwrap()), ScriptOutcome::Failure);
+    assert_eq!(outcome(truth(false).and(&truth(true)).unwrap()), ScriptOutcome::Failure);
+}
+
+#[test]
+pub fn or() {
+    let s = truth(false).or(&truth(true)).unwrap();
+    assert_eq!(s, Script::from(vec![Boolean(false), If, Boolean(true), Else, Boolean(true), Fi]));
+    assert_eq!(outcome(truth(false).or(&truth(true)).unwrap()), ScriptOutcome::Success);
+    assert_eq!(outcome(truth(true).or(&truth(false)).unwrap()), ScriptOutcome::Success);
+    assert_eq!(outcome(truth(false).or(&truth(false)).unwrap()), ScriptOutcome::Failure);
+}
+
+#[test]
+pub fn unbalanced_part() {
+    // the IF in the first part would pair up with the FI in the second
+    let a = Script::from(vec![Boolean(true), If]);
+    let b = Script::from(vec![Boolean(true), Fi]);
+    assert!(a.then(&b).is_err());
+    assert!(a.and(&b).is_err());
+}
+
+#[test]
+pub fn return_part() {
+    // "TRUE RETURN" would skip the signature check after it
+    let a = Script::from(vec![Boolean(true), Return]);
+    assert_eq!(a.and(&truth(false)), Err("cannot compose a script that uses RETURN".to_string()));
+}
+
+#[cfg(feature = "sign-ed25519")]
+#[test]
+pub fn parallel_signatures() {
+    let msg = b"commit";
+    let (sig1, pk1) = sign(1, msg);
+    let (sig2, pk2) = sign(2, msg);
+    let s = signature(&sig1, &pk1, msg)
+        .append_parallel_signature(&signature(&sig2, &pk2, msg))
+        .unwrap();
+    assert_eq!(outcome(s), ScriptOutcome::Success);
+
+    // a bad second signature fails the whole thing
+    let (bad, _) = sign(3, msg);
+    let s = signature(&sig1, &pk1, msg)
+        .append_parallel_signature(&signature(&bad, &pk2, msg))
+        .unwrap();
+    assert_eq!(outcome(s), ScriptOutcome::Failure);
+}
+
+#[cfg(feature = "sign-ed25519")]
+#[test]
+pub fn serial_endorsement() {
+    let msg = b"commit";
+    let (sig1, pk1) = sign(1, msg);
+    let (sig2, pk2) = sign(2, msg);
+
+    // the maintainer endorses the commit and both authors' signatures
+    let endorsed = vec![sig1.clone(), sig2.clone()];
+    let (sig3, pk3) = sign(3, &endorsement_message(msg, &endorsed));
+    let endorsement = Script::from(vec![
+        Binary(sig3),
+        Binary(pk3),
+        Binary(Bytes::copy_from_slice(msg))
+    ]);
+
+    let s = signature(&sig1, &pk1, msg)
+        .append_parallel_signature(&signature(&sig2, &pk2, msg))
+        .unwrap()
+        .append_serial_endorsement(&endorsement, &endorsed, Signing::Ed25519)
+        .unwrap();
+    assert_eq!(outcome(s), ScriptOutcome::Success);
+
+    // the endorsement doesn't cover a different set of signatures
+    let s = signature(&sig1, &pk1, msg)
+        .append_serial_endorsement(&endorsement, &endorsed[..1], Signing::Ed25519)
+        .unwrap();
+    assert_eq!(outcome(s), ScriptOutcome::Failure);
+}
+
+#[test]
+pub fn compose_ser_0() {
+    let s = truth(true).and(&truth(false)).unwrap().or(&truth(true)).unwrap();
+    let s = serde_json::to_string(&s).unwrap();
+    assert_eq!(s, r#""TRUE IF FALSE ELSE FALSE FI IF TRUE ELSE TRUE FI""#);
+}
+
+#[test]
+pub fn compose_de_0() {
+    let s1 = truth(true).and(&truth(false)).unwrap().or(&truth(true)).unwrap();
+    let s = r#""TRUE IF FALSE ELSE FALSE FI IF TRUE ELSE TRUE FI""#;
+    let s2: Script<CCLang> = serde_json::from_str(s).unwrap();
+    assert_eq!(s1, s2);
+}
@@ -63,6 +63,34 @@ pub fn concat() {
     }
 }
 
+#[test]
+pub fn concat_text() {
+    let script = Script::from(vec![
+        // push the expected merged text
+        Text("foobar".to_string()),
+
+        // push the left and right text
+        Text("foo".to_string()),
+        Text("bar".to_string()),
+
+        Concat,
+
+        // pop the merged text and the expected text and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
 #[test]
 pub fn slice() {
     let mut b = BytesMut::new();
@@ -0,0 +1,220 @@
+use bytes::Bytes;
+use cclang::{
+    shamir,
+    CryptoProvider,
+    Encryption,
+    Hashing,
+    Secret,
+    Signing
+};
+#[cfg(all(feature = "backend-rustcrypto", any(feature = "enc-xsalsa20", feature = "hash-sha2")))]
+use cclang::{
+    CCLang::{
+        Binary,
+        Decode,
+        EncodingId,
+        Text
+    },
+    Context,
+    Encoding,
+    Machine,
+    NullIO,
+    RustCrypto,
+    Script
+};
+#[cfg(all(feature = "backend-rustcrypto", feature = "enc-xsalsa20"))]
+use cclang::{
+    outcome::run_with,
+    CCLang::{
+        Encrypt,
+        EncryptionId,
+        Equal
+    },
+    ScriptOutcome
+};
+#[cfg(all(feature = "backend-rustcrypto", feature = "hash-sha2"))]
+use cclang::CCLang::{
+    HashingId,
+    Hash
+};
+
+// the same test data as the encryption tests
+#[cfg(feature = "enc-xsalsa20")]
+const MSG: &str = "455a8ecfd265c6e4ce63e590679a6e68b1e34b3112cdfe3e655fa47c545ae3f4f13bc066d289ec1d59eda208578d0040ad69d37411ae044583ca2c844ebcc099";
+const KEY: &str = "7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7";
+const NONCE: &str = "a65af86b4856df7f655ff71132af566a736b91e24a11e114";
+#[cfg(feature = "hash-sha2")]
+const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+const BOX: &str = "64a5fa3599adffef7ca387345760900d1fdb95b74b572b4ac42150f29f11105f7258e5bc135427e9f3c9b1340882de656a4fe7d789e85f9c0b9156ea8bc28692f29d0ba4991fed9daf956d174f75e058";
+
+fn hex(s: &str) -> Vec<u8> {
+    hex::decode(s).unwrap()
+}
+
+// the providers that are built in
+fn providers() -> Vec<Box<dyn CryptoProvider>> {
+    let mut providers: Vec<Box<dyn CryptoProvider>> = Vec::new();
+    #[cfg(feature = "backend-sodium")]
+    providers.push(Box::new(cclang::Sodium));
+    #[cfg(feature = "backend-rustcrypto")]
+    providers.push(Box::new(cclang::RustCrypto));
+    providers
+}
+
+// a provider that only does random numbers and doesn't have any randomness
+#[derive(Debug)]
+struct NotRandom;
+
+impl CryptoProvider for NotRandom {
+    fn hash(&self, _: Hashing, _: &[u8]) -> Bytes {
+        unimplemented!()
+    }
+
+    fn seal(&self, _: Encryption, _: &[u8], _: &[u8], _: &[u8]) -> Result<Bytes, String> {
+        unimplemented!()
+    }
+
+    fn open(&self, _: Encryption, _: &[u8], _: &[u8], _: &[u8]) -> Result<Vec<u8>, String> {
+        unimplemented!()
+    }
+
+    fn sign(&self, _: Signing, _: &[u8], _: &[u8]) -> Result<Bytes, String> {
+        unimplemented!()
+    }
+
+    fn verify(&self, _: Signing, _: &[u8], _: &[u8], _: &[u8]) -> Result<bool, String> {
+        unimplemented!()
+    }
+
+    fn keypair_from_seed(&self, _: Signing, _: &[u8]) -> Result<(Bytes, Secret), String> {
+        unimplemented!()
+    }
+
+    fn random(&self, buf: &mut [u8]) {
+        for b in buf.iter_mut() {
+            *b = 0;
+        }
+    }
+}
+
+#[cfg(any(feature = "hash-sha2", feature = "enc-xsalsa20", feature = "sign-ed25519"))]
+#[test]
+pub fn providers_agree() {
+    // the RustCrypto provider only has the algorithms whose features are on
+    let providers = providers();
+    for p in &providers {
+        #[cfg(feature = "hash-sha2")]
+        {
+            assert_eq!(hex::encode(p.hash(Hashing::SHA256, b"abc")), ABC_SHA256);
+            assert_eq!(p.hash(Hashing::SHA512, b"abc").len(), Hashing::SHA512.size());
+        }
+
+        #[cfg(feature = "enc-xsalsa20")]
+        {
+            let e = Encryption::XSalsa20Poly1305;
+            let ciphertext = p.seal(e, &hex(KEY), &hex(NONCE), &hex(MSG)).unwrap();
+            assert_eq!(ciphertext.as_ref(), hex(BOX).as_slice());
+            assert_eq!(p.open(e, &hex(KEY), &hex(NONCE), &ciphertext).unwrap(), hex(MSG));
+        }
+
+        #[cfg(feature = "sign-ed25519")]
+        {
+            let first = &providers[0];
+            let (pk, sk) = p.keypair_from_seed(Signing::Ed25519, &[7; 32]).unwrap();
+            assert_eq!((pk.clone(), sk.clone()), first.keypair_from_seed(Signing::Ed25519, &[7; 32]).unwrap());
+            let sig = p.sign(Signing::Ed25519, sk.as_ref(), b"msg").unwrap();
+            assert_eq!(sig, first.sign(Signing::Ed25519, sk.as_ref(), b"msg").unwrap());
+            assert_eq!(p.verify(Signing::Ed25519, &pk, b"msg", &sig), Ok(true));
+            assert_eq!(p.verify(Signing::Ed25519, &pk, b"other", &sig), Ok(false));
+        }
+    }
+}
+
+#[test]
+pub fn malformed() {
+    for p in providers() {
+        let e = Encryption::XSalsa20Poly1305;
+        assert!(p.seal(e, &[0; 16], &hex(NONCE), b"msg").is_err());
+        assert!(p.seal(e, &hex(KEY), &[0; 8], b"msg").is_err());
+
+        // a flipped bit fails to authenticate
+        let mut ciphertext = hex(BOX);
+        ciphertext[0] ^= 1;
+        assert!(p.open(e, &hex(KEY), &hex(NONCE), &ciphertext).is_err());
+
+        assert!(p.keypair_from_seed(Signing::Ed25519, &[7; 31]).is_err());
+        assert!(p.sign(Signing::Ed25519, &[7; 32], b"msg").is_err());
+        assert!(p.verify(Signing::Ed25519, &[7; 32], b"msg", &[0; 63]).is_err());
+    }
+}
+
+#[cfg(feature = "sign-ed25519")]
+#[test]
+pub fn small_order_key() {
+    // the identity point as the public key with R as the identity and S = 0
+    // satisfies the verification equation for any message
+    let mut pk = [0; 32];
+    pk[0] = 1;
+    let mut sig = [0; 64];
+    sig[0] = 1;
+    for p in providers() {
+        assert_eq!(p.verify(Signing::Ed25519, &pk, b"msg", &sig), Ok(false));
+        assert_eq!(p.verify(Signing::Ed25519, &pk, b"other", &sig), Ok(false));
+    }
+}
+
+#[cfg(all(feature = "backend-rustcrypto", feature = "enc-xsalsa20"))]
+#[test]
+pub fn context_provider() {
+    let script = Script::from(vec![
+        Binary(Bytes::from(hex(BOX))),
+        Text(MSG.to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+        Text(KEY.to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+        Text(NONCE.to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+        EncryptionId(Encryption::XSalsa20Poly1305),
+        Encrypt,
+        Equal
+    ]);
+    let mut ctx = Context::new();
+    ctx.provider(RustCrypto);
+    let mut machine = Machine::from(script);
+    assert_eq!(run_with(&mut machine, &NullIO, true, &ctx), ScriptOutcome::Success);
+}
+
+#[cfg(all(feature = "backend-rustcrypto", feature = "hash-sha2"))]
+#[test]
+pub fn context_hashing() {
+    let script = Script::from(vec![
+        Text("616263".to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+        HashingId(Hashing::SHA256),
+        Hash
+    ]);
+    let mut ctx = Context::new();
+    ctx.provider(RustCrypto);
+    let mut machine = Machine::from(script);
+    let mut result = ctx.execute(&mut machine, &NullIO).unwrap();
+    assert_eq!(result.pop(), Some(Binary(Bytes::from(hex(ABC_SHA256)))));
+}
+
+#[test]
+pub fn custom_provider() {
+    // with no randomness every coefficient is zero so each share holds the
+    // secret as is
+    let shares = shamir::split_with(&NotRandom, b"secret", 2, 3).unwrap();
+    for (i, share) in shares.iter().enumerate() {
+        assert_eq!(share[0], i as u8 + 1);
+        assert_eq!(&share[1..], b"secret");
+    }
+
+    // the default provider is random
+    let shares = shamir::split(b"secret", 2, 3).unwrap();
+    assert!(shares.iter().any(|s| &s[1..] != b"secret"));
+}
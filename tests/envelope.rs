@@ -0,0 +1,131 @@
+use bytes::Bytes;
+use cclang::{
+    CCLang::{
+        self,
+        GetVar,
+        GreaterThanEqual,
+        Index,
+        IntersectCount,
+        Text
+    },
+    Envelope,
+    Hashing,
+    Script,
+    ScriptId,
+    Secret,
+    Signing
+};
+#[cfg(feature = "backend-rustcrypto")]
+use cclang::RustCrypto;
+use sodiumoxide::crypto::sign::{
+    Seed,
+    keypair_from_seed
+};
+
+// the MAINTAINERS rule
+fn policy() -> Script<CCLang> {
+    Script::from(vec![
+        Text("signers".to_string()),
+        GetVar,
+        Text("maintainers".to_string()),
+        GetVar,
+        IntersectCount,
+        Index(2),
+        GreaterThanEqual
+    ])
+}
+
+// the key pair made from the seed byte
+fn keys(seed: u8) -> (Bytes, Secret) {
+    let (pk, sk) = keypair_from_seed(&Seed([seed; 32]));
+    (Bytes::copy_from_slice(pk.as_ref()), Secret::from(sk.as_ref()))
+}
+
+fn signed(seeds: &[u8]) -> Envelope {
+    let mut envelope = Envelope::new(&policy(), Hashing::SHA256).unwrap();
+    for seed in seeds {
+        envelope.sign(Signing::Ed25519, &keys(*seed).1).unwrap();
+    }
+    envelope
+}
+
+fn trusted() -> Vec<Bytes> {
+    vec![keys(1).0, keys(2).0, keys(3).0]
+}
+
+#[test]
+pub fn verify() {
+    let envelope = signed(&[1, 3]);
+    assert_eq!(envelope.id(), &policy().id(Hashing::SHA256).unwrap());
+    assert_eq!(envelope.signatures().len(), 2 as usize);
+    assert_eq!(envelope.verify(&trusted(), 2), Ok(policy()));
+}
+
+#[test]
+pub fn threshold() {
+    // the same signer twice and an untrusted signer don't count
+    let envelope = signed(&[1, 1, 4]);
+    assert_eq!(envelope.verify(&trusted(), 2), Err("envelope has 1 of the 2 required trusted signatures".to_string()));
+    assert!(envelope.verify(&trusted(), 1).is_ok());
+
+    // an unsigned envelope doesn't pass with nothing required of it
+    let unsigned = signed(&[]);
+    assert_eq!(unsigned.verify(&trusted(), 0), Err("envelope threshold must be at least 1".to_string()));
+    assert_eq!(unsigned.verify(&[], 1), Err("envelope needs at least one trusted key".to_string()));
+    assert_eq!(envelope.verify(&[], 0), Err("envelope threshold must be at least 1".to_string()));
+}
+
+#[cfg(feature = "backend-rustcrypto")]
+#[test]
+pub fn provider() {
+    let p = RustCrypto;
+    let mut envelope = Envelope::new_with(&p, &policy(), Hashing::SHA256).unwrap();
+    envelope.sign_with(&p, Signing::Ed25519, &keys(1).1).unwrap();
+    envelope.sign_with(&p, Signing::Ed25519, &keys(2).1).unwrap();
+    assert_eq!(envelope.id(), &policy().id_with(&p, Hashing::SHA256).unwrap());
+    assert_eq!(envelope, signed(&[1, 2]));
+    assert_eq!(envelope.verify_with(&p, &trusted(), 2), Ok(policy()));
+}
+
+#[test]
+pub fn tampered_script() {
+    let envelope = signed(&[1, 2]);
+    let mut json: serde_json::Value = serde_json::to_value(&envelope).unwrap();
+
+    // change the threshold in the script from 2 to 1
+    let script = json["script"].as_str().unwrap().replace("0308000000000000000200", "0308000000000000000100");
+    json["script"] = serde_json::Value::String(script);
+    let tampered: Envelope = serde_json::from_value(json).unwrap();
+    assert_eq!(tampered.verify(&trusted(), 2), Err("envelope id does not match the script".to_string()));
+}
+
+#[test]
+pub fn tampered_id() {
+    // a new id for the changed script breaks the signatures
+    let changed = Script::from(vec![Index(1)]);
+    let mut json: serde_json::Value = serde_json::to_value(&signed(&[1, 2])).unwrap();
+    json["script"] = serde_json::Value::String(hex::encode(cclang::canonical::encode(&changed).unwrap()));
+    json["id"] = serde_json::Value::String(hex::encode(changed.id(Hashing::SHA256).unwrap()));
+    let tampered: Envelope = serde_json::from_value(json).unwrap();
+    assert_eq!(tampered.verify(&trusted(), 2), Err("envelope has an invalid signature".to_string()));
+}
+
+#[test]
+pub fn envelope_ser_0() {
+    let envelope = signed(&[1]);
+    let s = serde_json::to_string(&envelope).unwrap();
+    let v: serde_json::Value = serde_json::from_str(&s).unwrap();
+    assert_eq!(v["hashing"], "SHA256");
+    assert_eq!(v["id"], hex::encode(policy().id(Hashing::SHA256).unwrap()));
+    assert_eq!(v["signatures"][0]["signing"], "Ed25519");
+    assert_eq!(v["signatures"][0]["key"], hex::encode(keys(1).0));
+}
+
+#[test]
+pub fn envelope_de_0() {
+    let e1 = signed(&[1, 2]);
+    let s = serde_json::to_string(&e1).unwrap();
+    let e2: Envelope = serde_json::from_str(&s).unwrap();
+    assert_eq!(e1, e2);
+    assert_eq!(e2.verify(&trusted(), 2), Ok(policy()));
+}
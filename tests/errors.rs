@@ -0,0 +1,57 @@
+use cclang::{
+    CCLang::{
+        Decode,
+        EncodingId,
+        Error,
+        Index,
+        Text
+    },
+    Encoding,
+    Machine,
+    NullIO,
+    Script
+};
+
+#[test]
+pub fn decoding_bad_hex() {
+    let script = Script::from(vec![
+        Text("not hex".to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+        Index(1)
+    ]);
+    let mut machine = Machine::from(script);
+    assert!(machine.execute(&NullIO).is_none());
+    assert_eq!(machine.pop(), Some(Error("failed to decode Hex text".to_string())));
+}
+
+#[test]
+pub fn decoding_bad_multibase_prefix() {
+    let script = Script::from(vec![
+        Text("qabc".to_string()),
+        EncodingId(Encoding::Multibase),
+        Decode
+    ]);
+    let mut machine = Machine::from(script);
+    assert!(machine.execute(&NullIO).is_none());
+    assert_eq!(machine.pop(), Some(Error("unknown multibase prefix".to_string())));
+}
+
+#[test]
+pub fn error_aborts() {
+    // an Error in a script stops it the same as a failed opcode
+    let script = Script::from(vec![Error("stop".to_string()), Index(1)]);
+    let mut machine = Machine::from(script);
+    assert!(machine.execute(&NullIO).is_none());
+    assert_eq!(machine.pop(), Some(Error("stop".to_string())));
+}
+
+#[test]
+pub fn error_display() {
+    // the stack of a failed machine can be printed
+    let script = Script::from(vec![Index(1), Text("zz".to_string()), EncodingId(Encoding::Hex), Decode]);
+    let mut machine = Machine::from(script);
+    assert!(machine.execute(&NullIO).is_none());
+    assert_eq!(format!("{}", machine.pop().unwrap()), "ERROR(failed to decode Hex text)");
+    assert_eq!(format!("{}", Error("stop".to_string())), "ERROR(stop)");
+}
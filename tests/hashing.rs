@@ -1,12 +1,10 @@
+#[cfg(feature = "hash-sha2")]
 use bytes::{ 
     BufMut,
     BytesMut
 };
 use cclang::{
     CCLang::{
-        self,
-        Binary,
-        Boolean,
         Decode,
         EncodingId,
         Equal,
@@ -16,10 +14,18 @@ use cclang::{
     },
     Encoding,
     Hashing,
-    Machine,
-    NullIO,
     Script
 };
+#[cfg(feature = "hash-sha2")]
+use cclang::{
+    CCLang::{
+        self,
+        Binary,
+        Boolean
+    },
+    Machine,
+    NullIO
+};
 
 /* TEST DATA
 msg: fde223e5919f671b0423ae3fa39f3f91992066b7f134323fbda965f7b903080a535a7e5315bf77a980b760d80de4e1a0c20487485cd7f7274480a4f3269aa9ef
@@ -29,6 +35,7 @@ SHA256: d19242361d4e1faacb8f7561b7fc2eaf02b09bb9a449377d944a0e0142851b21
 SHA512: 7ccd257b67b0ec6b68a68640575494cfec9792ade654fbb4f8fddf05c80bc183eff14c0056e9db0d52faf03aca9c671c63147bf6c8e8ef8beb75548ed7409c5b
 */
 
+#[cfg(feature = "hash-sha2")]
 #[test]
 pub fn hashing_sha256() {
     let mut b = BytesMut::new();
@@ -63,6 +70,7 @@ pub fn hashing_sha256() {
     }
 }
 
+#[cfg(feature = "hash-sha2")]
 #[test]
 pub fn hashing_sha512() {
     let mut b = BytesMut::new();
@@ -139,6 +147,7 @@ pub fn hashing_sha512_ser_0() {
     assert_eq!(s, r#""7ccd257b67b0ec6b68a68640575494cfec9792ade654fbb4f8fddf05c80bc183eff14c0056e9db0d52faf03aca9c671c63147bf6c8e8ef8beb75548ed7409c5b fde223e5919f671b0423ae3fa39f3f91992066b7f134323fbda965f7b903080a535a7e5315bf77a980b760d80de4e1a0c20487485cd7f7274480a4f3269aa9ef Hex DECODE SHA512 HASH =""#);
 }
 
+#[cfg(feature = "hash-sha2")]
 #[test]
 pub fn hashing_sha256_de_0() {
     let s1 = Script::from(vec![
@@ -161,6 +170,7 @@ pub fn hashing_sha256_de_0() {
     assert_eq!(s1, s2);
 }
 
+#[cfg(feature = "hash-sha2")]
 #[test]
 pub fn hashing_sha512_de_0() {
     let s1 = Script::from(vec![
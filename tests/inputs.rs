@@ -0,0 +1,149 @@
+use bytes::{
+    BufMut,
+    BytesMut
+};
+use cclang::{
+    check::check,
+    CCLang::{
+        self,
+        Add,
+        Binary,
+        Boolean,
+        Concat,
+        EncodingId,
+        Equal,
+        Error,
+        GetVar,
+        GreaterThanEqual,
+        Index,
+        IntersectCount,
+        List,
+        Pop,
+        Text
+    },
+    Context,
+    Encoding,
+    ExecutionPolicy,
+    Machine,
+    NullIO,
+    Script
+};
+
+fn binary(h: &str) -> CCLang {
+    let mut b = BytesMut::new();
+    b.put_slice(&hex::decode(h.to_string()).unwrap());
+    Binary(b.freeze())
+}
+
+fn name(n: &str) -> CCLang {
+    Text(n.to_string())
+}
+
+#[test]
+pub fn getvar() {
+    let script = Script::from(vec![
+        name("commit_hash"),
+        GetVar,
+        binary("b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"),
+        Equal
+    ]);
+    let mut ctx = Context::new();
+    ctx.bind("commit_hash", binary("b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"));
+    let mut machine = Machine::from(script);
+    let mut result = ctx.execute(&mut machine, &NullIO).unwrap();
+    assert_eq!(result.size(), 1 as usize);
+    assert_eq!(result.pop(), Some(Boolean(true)));
+}
+
+#[test]
+pub fn policy() {
+    // the stored 2-of-3 policy reads the signers and maintainers from the hook
+    let script = Script::from(vec![
+        name("signers"),
+        GetVar,
+        name("maintainers"),
+        GetVar,
+        IntersectCount,
+        Index(2),
+        GreaterThanEqual
+    ]);
+    let mut ctx = Context::new();
+    ctx.bind("signers", List(vec![name("bob"), name("carol")]))
+        .bind("maintainers", List(vec![name("alice"), name("bob"), name("carol")]));
+    let mut machine = Machine::from(script);
+    let mut result = ctx.execute(&mut machine, &NullIO).unwrap();
+    assert_eq!(result.pop(), Some(Boolean(true)));
+}
+
+#[test]
+pub fn unbound() {
+    let mut machine = Machine::from(Script::from(vec![name("missing"), GetVar]));
+    assert!(Context::new().execute(&mut machine, &NullIO).is_none());
+    assert_eq!(machine.pop(), Some(Error("input missing is not bound".to_string())));
+}
+
+#[test]
+pub fn rejected() {
+    // run GETVAR for the name and return the error it stopped with
+    let read = |ctx: &Context, n: &str| {
+        let mut machine = Machine::from(Script::from(vec![name(n), GetVar]));
+        assert!(ctx.execute(&mut machine, &NullIO).is_none());
+        machine.pop()
+    };
+    let mut policy = ExecutionPolicy::new();
+    policy.allow_encodings(&[Encoding::Base64]);
+    let mut ctx = Context::new();
+    ctx.policy(policy)
+        .bind("op", Add)
+        .bind("list", List(vec![Index(1), Add]))
+        .bind("encoding", EncodingId(Encoding::Hex))
+        .bind("encodings", List(vec![EncodingId(Encoding::Base64), EncodingId(Encoding::Hex)]));
+
+    // only data can be bound
+    assert_eq!(read(&ctx, "op"), Some(Error("input op is not data".to_string())));
+    assert_eq!(read(&ctx, "list"), Some(Error("input list is not data".to_string())));
+
+    // and an id has to be allowed by the policy
+    assert_eq!(read(&ctx, "encoding"), Some(Error("Hex is not allowed by the execution policy".to_string())));
+    assert_eq!(read(&ctx, "encodings"), Some(Error("Base64 Hex 2 PACK is not allowed by the execution policy".to_string())));
+}
+
+#[test]
+pub fn check_inputs() {
+    let script = Script::from(vec![
+        name("signers"),
+        GetVar,
+        name("maintainers"),
+        GetVar,
+        IntersectCount,
+        name("threshold"),
+        GetVar,
+        GreaterThanEqual,
+        name("signers"),
+        GetVar,
+        Pop
+    ]);
+    let report = check(&script).unwrap();
+    assert_eq!(report.inputs, vec!["maintainers".to_string(), "signers".to_string(), "threshold".to_string()]);
+}
+
+#[test]
+pub fn check_computed_name() {
+    let script = Script::from(vec![name("sign"), name("ers"), Concat, GetVar]);
+    assert_eq!(check(&script), Err("GETVAR at 3 needs a literal name".to_string()));
+}
+
+#[test]
+pub fn getvar_ser_0() {
+    let script = Script::from(vec![name("commit_hash"), GetVar]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""commit_hash GETVAR""#);
+}
+
+#[test]
+pub fn getvar_de_0() {
+    let s1 = Script::from(vec![name("commit_hash"), GetVar]);
+    let s = r#""commit_hash GETVAR""#;
+    let s2: Script<CCLang> = serde_json::from_str(s).unwrap();
+    assert_eq!(s1, s2);
+}
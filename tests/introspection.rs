@@ -0,0 +1,171 @@
+use bytes::Bytes;
+use cclang::{
+    CCLang::{
+        self,
+        Binary,
+        Boolean,
+        Decode,
+        Else,
+        EncodingId,
+        Equal,
+        Error,
+        Fi,
+        If,
+        Index,
+        IsBinary,
+        IsBoolean,
+        IsIndex,
+        IsSecret,
+        IsText,
+        Len,
+        Pop,
+        Text,
+        ToSecret,
+        TypeOf
+    },
+    Encoding,
+    Machine,
+    NullIO,
+    Script
+};
+#[cfg(feature = "hash-sha2")]
+use cclang::{
+    CCLang::HashingId,
+    Hashing
+};
+
+// run the script and return the stack from the bottom to the top
+fn run(script: Vec<CCLang>) -> Vec<CCLang> {
+    let mut machine = Machine::from(Script::from(script));
+    let mut result = machine.execute(&NullIO).unwrap();
+    let mut items = Vec::new();
+    while let Some(i) = result.pop() {
+        items.insert(0, i);
+    }
+    items
+}
+
+#[test]
+pub fn len() {
+    let b = Binary(Bytes::from(&b"Hello!"[..]));
+    assert_eq!(run(vec![b.clone(), Len]), vec![b, Index(6)]);
+
+    // text length is in UTF-8 bytes
+    let t = Text("h\u{e9}llo".to_string());
+    assert_eq!(run(vec![t.clone(), Len]), vec![t, Index(6)]);
+}
+
+#[test]
+pub fn len_key_check() {
+    // check that a decoded key is 32 bytes before using it
+    let result = run(vec![
+        Text("0adb80d2fc4d74adb99059a596ba21706dada1e29fd855a664ce815f88e6b169".to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+        Len,
+        Index(32),
+        Equal,
+        If,
+            Text("ok".to_string()),
+        Else,
+            Pop,
+            Text("bad key".to_string()),
+        Fi
+    ]);
+    assert_eq!(result.len(), 2 as usize);
+    assert_eq!(result[1], Text("ok".to_string()));
+}
+
+#[test]
+pub fn len_wrong_type() {
+    let mut machine = Machine::from(Script::from(vec![Index(1), Len]));
+    assert!(machine.execute(&NullIO).is_none());
+    match machine.pop() {
+        Some(Error(e)) => assert_eq!(e, "cannot take the length of Index"),
+        _ => panic!()
+    }
+    assert_eq!(machine.pop(), Some(Index(1)));
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn typeof_tags() {
+    let cases = vec![
+        (Boolean(true), "Boolean"),
+        (Binary(Bytes::from(&b"Hello!"[..])), "Binary"),
+        (Text("Hello!".to_string()), "Text"),
+        (Index(1), "Index"),
+        (HashingId(Hashing::SHA256), "HashingId"),
+        (EncodingId(Encoding::Hex), "EncodingId")
+    ];
+    for (v, tag) in cases {
+        assert_eq!(run(vec![v.clone(), TypeOf]), vec![v, Text(tag.to_string())]);
+    }
+}
+
+#[test]
+pub fn typeof_secret() {
+    let result = run(vec![Text("key".to_string()), ToSecret, TypeOf]);
+    assert_eq!(result[1], Text("Secret".to_string()));
+}
+
+#[test]
+pub fn predicates() {
+    let b = Binary(Bytes::from(&b"Hello!"[..]));
+    assert_eq!(run(vec![b.clone(), IsBinary]), vec![b.clone(), Boolean(true)]);
+    assert_eq!(run(vec![b.clone(), IsText]), vec![b.clone(), Boolean(false)]);
+    assert_eq!(run(vec![b.clone(), IsBoolean]), vec![b.clone(), Boolean(false)]);
+    assert_eq!(run(vec![b.clone(), IsIndex]), vec![b.clone(), Boolean(false)]);
+    assert_eq!(run(vec![b.clone(), IsSecret]), vec![b, Boolean(false)]);
+
+    let t = Text("Hello!".to_string());
+    assert_eq!(run(vec![t.clone(), IsText]), vec![t, Boolean(true)]);
+    assert_eq!(run(vec![Boolean(false), IsBoolean]), vec![Boolean(false), Boolean(true)]);
+    assert_eq!(run(vec![Index(0), IsIndex]), vec![Index(0), Boolean(true)]);
+
+    let result = run(vec![Text("key".to_string()), ToSecret, IsSecret]);
+    assert_eq!(result[1], Boolean(true));
+}
+
+#[test]
+pub fn introspection_empty_stack() {
+    let mut machine = Machine::from(Script::from(vec![TypeOf]));
+    assert!(machine.execute(&NullIO).is_none());
+    match machine.pop() {
+        Some(Error(e)) => assert_eq!(e, "stack underflow"),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn introspection_ser_0() {
+    let script = Script::from(vec![
+        Text("foo".to_string()),
+        Len,
+        TypeOf,
+        IsBinary,
+        IsText,
+        IsBoolean,
+        IsIndex,
+        IsSecret
+    ]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""foo LEN TYPEOF ISBINARY ISTEXT ISBOOLEAN ISINDEX ISSECRET""#);
+}
+
+#[test]
+pub fn introspection_de_0() {
+    let s1 = Script::from(vec![
+        Text("foo".to_string()),
+        Len,
+        TypeOf,
+        IsBinary,
+        IsText,
+        IsBoolean,
+        IsIndex,
+        IsSecret
+    ]);
+    let s = r#""foo LEN TYPEOF ISBINARY ISTEXT ISBOOLEAN ISINDEX ISSECRET""#;
+    let s2: Script<CCLang> = serde_json::from_str(s).unwrap();
+    assert_eq!(s1, s2);
+}
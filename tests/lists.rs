@@ -0,0 +1,202 @@
+use bytes::{
+    BufMut,
+    BytesMut
+};
+use cclang::{
+    CCLang::{
+        self,
+        Binary,
+        Boolean,
+        Contains,
+        Error,
+        GreaterThanEqual,
+        Index,
+        IntersectCount,
+        Len,
+        List,
+        Pack,
+        Text,
+        TypeOf,
+        Unique,
+        Unpack
+    },
+    Machine,
+    NullIO,
+    Script
+};
+
+// run the script and return the stack from the bottom to the top
+fn run(script: Vec<CCLang>) -> Vec<CCLang> {
+    let mut machine = Machine::from(Script::from(script));
+    let mut result = machine.execute(&NullIO).unwrap();
+    let mut items = Vec::new();
+    while let Some(i) = result.pop() {
+        items.insert(0, i);
+    }
+    items
+}
+
+fn key(h: &str) -> CCLang {
+    let mut b = BytesMut::new();
+    b.put_slice(&hex::decode(h.to_string()).unwrap());
+    Binary(b.freeze())
+}
+
+const ALICE: &str = "2e6fcce36701dc791488e0d0b1745cc1e33a4c1c9fcc41c63bd343dbbe0970e6";
+const BOB: &str = "19bf44096984cdfe8541bac167dc3b96c85086aa30b6b6cb0c5c38ad703166e1";
+const CAROL: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+const MALLORY: &str = "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c";
+
+#[test]
+pub fn pack() {
+    let result = run(vec![
+        Index(1),
+        Text("two".to_string()),
+        Index(3),
+        Index(3),
+        Pack,
+        Len
+    ]);
+    assert_eq!(result, vec![
+        List(vec![Index(1), Text("two".to_string()), Index(3)]),
+        Index(3)
+    ]);
+}
+
+#[test]
+pub fn pack_empty() {
+    let result = run(vec![Index(0), Pack, TypeOf]);
+    assert_eq!(result, vec![List(vec![]), Text("List".to_string())]);
+}
+
+#[test]
+pub fn pack_underflow() {
+    let mut machine = Machine::from(Script::from(vec![Index(1), Index(2), Pack]));
+    assert!(machine.execute(&NullIO).is_none());
+    assert_eq!(machine.pop(), Some(Error("stack underflow".to_string())));
+    assert_eq!(machine.pop(), Some(Index(1)));
+}
+
+#[test]
+pub fn unpack() {
+    let result = run(vec![
+        Index(1),
+        Index(2),
+        Index(2),
+        Pack,
+        Unpack
+    ]);
+    assert_eq!(result, vec![Index(1), Index(2), Index(2)]);
+}
+
+#[test]
+pub fn contains() {
+    let result = run(vec![
+        key(ALICE), key(BOB), Index(2), Pack,
+        key(BOB),
+        Contains
+    ]);
+    assert_eq!(result, vec![Boolean(true)]);
+
+    let result = run(vec![
+        key(ALICE), key(BOB), Index(2), Pack,
+        key(MALLORY),
+        Contains
+    ]);
+    assert_eq!(result, vec![Boolean(false)]);
+}
+
+#[test]
+pub fn unique() {
+    let result = run(vec![
+        Index(1), Index(2), Index(1), Index(3), Index(2),
+        Index(5),
+        Pack,
+        Unique
+    ]);
+    assert_eq!(result, vec![List(vec![Index(1), Index(2), Index(3)])]);
+}
+
+#[test]
+pub fn strict_equality() {
+    // values of different types are never the same item, even though
+    // 'TRUE 5 =' is true
+    let result = run(vec![Boolean(true), Index(5), Index(2), Pack, Unique]);
+    assert_eq!(result, vec![List(vec![Boolean(true), Index(5)])]);
+
+    let result = run(vec![Index(5), Index(1), Pack, Boolean(true), Contains]);
+    assert_eq!(result, vec![Boolean(false)]);
+
+    let result = run(vec![
+        Index(1), Index(1), Pack,
+        Boolean(true), Index(1), Pack,
+        IntersectCount
+    ]);
+    assert_eq!(result, vec![Index(0)]);
+}
+
+#[test]
+pub fn m_of_n() {
+    // the keys for the valid signatures, with bob's counted twice
+    let signers = vec![key(BOB), key(MALLORY), key(BOB), key(CAROL), Index(4), Pack];
+
+    // the 2-of-3 check script from the MAINTAINERS file
+    let maintainers = vec![
+        key(ALICE), key(BOB), key(CAROL), Index(3), Pack,
+        IntersectCount,
+        Index(2),
+        GreaterThanEqual
+    ];
+    let result = run(signers.into_iter().chain(maintainers).collect());
+    assert_eq!(result, vec![Boolean(true)]);
+
+    // bob signing twice doesn't meet the threshold
+    let signers = vec![key(BOB), key(MALLORY), key(BOB), Index(3), Pack];
+    let maintainers = vec![
+        key(ALICE), key(BOB), key(CAROL), Index(3), Pack,
+        IntersectCount,
+        Index(2),
+        GreaterThanEqual
+    ];
+    let result = run(signers.into_iter().chain(maintainers).collect());
+    assert_eq!(result, vec![Boolean(false)]);
+}
+
+#[test]
+pub fn list_ser_0() {
+    let script = Script::from(vec![
+        List(vec![Index(1), Index(2)]),
+        Index(2),
+        Contains
+    ]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""1 2 2 PACK 2 CONTAINS""#);
+}
+
+#[test]
+pub fn list_ser_1() {
+    let script = Script::from(vec![
+        Index(0),
+        Pack,
+        Unpack,
+        Unique,
+        IntersectCount
+    ]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""0 PACK UNPACK UNIQUE INTERSECTCOUNT""#);
+}
+
+#[test]
+pub fn list_de_0() {
+    let s1 = Script::from(vec![
+        Index(1),
+        Index(2),
+        Index(2),
+        Pack,
+        Index(2),
+        Contains
+    ]);
+    let s = r#""1 2 2 PACK 2 CONTAINS""#;
+    let s2: Script<CCLang> = serde_json::from_str(s).unwrap();
+    assert_eq!(s1, s2);
+}
@@ -2,6 +2,8 @@ use bytes::Bytes;
 use cclang::{
     CCLang::{
         self,
+        And,
+        Assert,
         Binary,
         Boolean,
         Equal,
@@ -10,7 +12,9 @@ use cclang::{
         Index,
         LessThan,
         LessThanEqual,
+        Not,
         NotEqual,
+        Or,
         Text
     },
     Machine,
@@ -118,6 +122,66 @@ pub fn equal_4() {
     }
 }
 
+#[test]
+pub fn equal_5() {
+    let script = Script::from(vec![
+        Binary(Bytes::from(&b"Hello!"[..])),
+        Binary(Bytes::from(&b"Hello?"[..])),
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value false
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, false),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn equal_6() {
+    let script = Script::from(vec![
+        Binary(Bytes::from(&b"Hello!"[..])),
+        Binary(Bytes::from(&b"Hello"[..])),
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value false
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, false),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn equal_7() {
+    let script = Script::from(vec![
+        Binary(Bytes::from(&b""[..])),
+        Binary(Bytes::from(&b""[..])),
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
 #[test]
 pub fn equal_ser_0() {
     let script = Script::from(vec![
@@ -264,6 +328,46 @@ pub fn not_equal_4() {
     }
 }
 
+#[test]
+pub fn not_equal_5() {
+    let script = Script::from(vec![
+        Binary(Bytes::from(&b"Hello!"[..])),
+        Binary(Bytes::from(&b"Hello!"[..])),
+        NotEqual
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value false
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, false),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn not_equal_6() {
+    let script = Script::from(vec![
+        Binary(Bytes::from(&b"Hello"[..])),
+        Binary(Bytes::from(&b"Hello!"[..])),
+        NotEqual
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
 #[test]
 pub fn less_than_0() {
     let script = Script::from(vec![
@@ -665,3 +769,110 @@ pub fn greater_than_equal_4() {
 }
 
 
+
+fn run(script: Vec<CCLang>) -> bool {
+    let mut machine = Machine::from(Script::from(script));
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    match result.pop() {
+        Some(Boolean(b)) => b,
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn and() {
+    assert_eq!(run(vec![Boolean(true), Boolean(true), And]), true);
+    assert_eq!(run(vec![Boolean(true), Boolean(false), And]), false);
+    assert_eq!(run(vec![Boolean(false), Boolean(true), And]), false);
+    assert_eq!(run(vec![Boolean(false), Boolean(false), And]), false);
+}
+
+#[test]
+pub fn or() {
+    assert_eq!(run(vec![Boolean(true), Boolean(true), Or]), true);
+    assert_eq!(run(vec![Boolean(true), Boolean(false), Or]), true);
+    assert_eq!(run(vec![Boolean(false), Boolean(true), Or]), true);
+    assert_eq!(run(vec![Boolean(false), Boolean(false), Or]), false);
+}
+
+#[test]
+pub fn not() {
+    assert_eq!(run(vec![Boolean(true), Not]), false);
+    assert_eq!(run(vec![Boolean(false), Not]), true);
+}
+
+#[test]
+pub fn policy() {
+    // (alice AND bob) OR carol with alice and carol's checks passing
+    assert_eq!(run(vec![Boolean(true), Boolean(false), And, Boolean(true), Or]), true);
+
+    // (alice AND bob) OR carol with only alice's check passing
+    assert_eq!(run(vec![Boolean(true), Boolean(false), And, Boolean(false), Or]), false);
+}
+
+#[test]
+pub fn assert_true() {
+    // a passing ASSERT consumes the boolean and execution continues
+    let script = Script::from(vec![
+        Index(1),
+        Index(1),
+        Equal,
+        Assert,
+        Text("continued".to_string())
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+    assert_eq!(result.size(), 1 as usize);
+    assert_eq!(result.pop(), Some(Text("continued".to_string())));
+}
+
+#[test]
+pub fn assert_false() {
+    // a failing ASSERT stops the script with FALSE on top of the stack
+    let script = Script::from(vec![
+        Index(1),
+        Index(2),
+        Equal,
+        Assert,
+        Text("continued".to_string())
+    ]);
+    let mut machine = Machine::from(script);
+    assert!(machine.execute(&NullIO).is_none());
+    assert_eq!(machine.pop(), Some(Boolean(false)));
+    assert_eq!(machine.pop(), None);
+}
+
+#[test]
+pub fn boolean_ser_0() {
+    let script = Script::from(vec![
+        Boolean(true),
+        Boolean(false),
+        And,
+        Boolean(true),
+        Or,
+        Not,
+        Assert
+    ]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""TRUE FALSE AND TRUE OR NOT ASSERT""#);
+}
+
+#[test]
+pub fn boolean_de_0() {
+    let s1 = Script::from(vec![
+        Boolean(true),
+        Boolean(false),
+        And,
+        Boolean(true),
+        Or,
+        Not,
+        Assert
+    ]);
+    let s = r#""TRUE FALSE AND TRUE OR NOT ASSERT""#;
+    let s2: Script<CCLang> = serde_json::from_str(s).unwrap();
+    assert_eq!(s1, s2);
+}
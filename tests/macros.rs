@@ -0,0 +1,207 @@
+use cclang::{
+    check::check,
+    CCLang::{
+        self,
+        Add,
+        Define,
+        Dup,
+        EndDefine,
+        Error,
+        GetVar,
+        Index,
+        Invoke,
+        Mul,
+        Text
+    },
+    Machine,
+    Macros,
+    NullIO,
+    Script
+};
+
+// run the script and return the stack from the bottom to the top
+fn run(mut machine: Machine<CCLang>) -> Vec<CCLang> {
+    let mut result = machine.execute(&NullIO).unwrap();
+    let mut items = Vec::new();
+    while let Some(i) = result.pop() {
+        items.insert(0, i);
+    }
+    items
+}
+
+fn name(n: &str) -> CCLang {
+    Text(n.to_string())
+}
+
+#[test]
+pub fn define() {
+    let script = Script::from(vec![
+        Define, name("square"), Dup, Mul, EndDefine,
+        Index(3),
+        Invoke, name("square"),
+        Invoke, name("square")
+    ]);
+    let machine = Macros::new().machine(&script).unwrap();
+    assert_eq!(run(machine), vec![Index(81)]);
+}
+
+#[test]
+pub fn expand() {
+    let script = Script::from(vec![
+        Define, name("square"), Dup, Mul, EndDefine,
+        Define, name("quad"), Invoke, name("square"), Invoke, name("square"), EndDefine,
+        Index(2),
+        Invoke, name("quad")
+    ]);
+    let expanded = Macros::new().expand(&script).unwrap();
+    assert_eq!(expanded, Script::from(vec![Index(2), Dup, Mul, Dup, Mul]));
+
+    // the expanded script passes the checker
+    assert_eq!(check(&expanded).unwrap().cost, 5);
+}
+
+#[test]
+pub fn library() {
+    let library = Script::from(vec![
+        Define, name("inc"), Index(1), Add, EndDefine
+    ]);
+    let mut macros = Macros::new();
+    macros.load(&library).unwrap();
+    assert!(macros.contains("inc"));
+
+    let script = Script::from(vec![Index(1), Invoke, name("inc"), Invoke, name("inc")]);
+    assert_eq!(run(macros.machine(&script).unwrap()), vec![Index(3)]);
+
+    // definitions in a script don't leak into the table
+    let script = Script::from(vec![
+        Define, name("dec"), Index(-1), Add, EndDefine,
+        Index(1), Invoke, name("dec")
+    ]);
+    assert_eq!(run(macros.machine(&script).unwrap()), vec![Index(0)]);
+    assert!(!macros.contains("dec"));
+}
+
+#[test]
+pub fn library_only_definitions() {
+    let library = Script::from(vec![
+        Define, name("inc"), Index(1), Add, EndDefine,
+        Index(1)
+    ]);
+    assert!(Macros::new().load(&library).is_err());
+}
+
+#[test]
+pub fn recursive() {
+    let script = Script::from(vec![
+        Define, name("forever"), Dup, Invoke, name("forever"), EndDefine
+    ]);
+    assert_eq!(Macros::new().expand(&script), Err("macro forever is recursive".to_string()));
+}
+
+#[test]
+pub fn redefine() {
+    let script = Script::from(vec![
+        Define, name("one"), Index(1), EndDefine,
+        Define, name("one"), Index(2), EndDefine
+    ]);
+    assert_eq!(Macros::new().expand(&script), Err("macro one is already defined".to_string()));
+}
+
+#[test]
+pub fn nested_define() {
+    let script = Script::from(vec![
+        Define, name("outer"), Define, name("inner"), Dup, EndDefine, EndDefine
+    ]);
+    assert!(Macros::new().expand(&script).is_err());
+}
+
+#[test]
+pub fn unbalanced() {
+    let missing = Script::from(vec![Define, name("one"), Index(1)]);
+    assert!(Macros::new().expand(&missing).is_err());
+    let unexpected = Script::from(vec![Index(1), EndDefine]);
+    assert_eq!(Macros::new().expand(&unexpected), Err("unexpected ENDDEF at 1".to_string()));
+    let unnamed = Script::from(vec![Define, Index(1), EndDefine]);
+    assert!(Macros::new().expand(&unnamed).is_err());
+}
+
+#[test]
+pub fn too_long() {
+    // each macro doubles the one before it
+    let mut script = vec![Define, name("m0"), Index(1), Index(1), EndDefine];
+    for i in 1..30 {
+        script.extend(vec![Define, name(&format!("m{}", i)), Invoke, name(&format!("m{}", i - 1)), Invoke, name(&format!("m{}", i - 1)), EndDefine]);
+    }
+    let script = Script::from(script);
+    assert_eq!(Macros::new().expand(&script), Err("macro expansion is longer than 65536 instructions".to_string()));
+}
+
+#[test]
+pub fn text_is_data() {
+    // text equal to a macro name is left alone, only INVOKE expands a macro
+    let script = Script::from(vec![
+        Define, name("threshold"), Index(2), EndDefine,
+        name("threshold"),
+        GetVar,
+        Invoke, name("threshold")
+    ]);
+    let expanded = Macros::new().expand(&script).unwrap();
+    assert_eq!(expanded, Script::from(vec![name("threshold"), GetVar, Index(2)]));
+}
+
+#[test]
+pub fn invoke() {
+    let undefined = Script::from(vec![Invoke, name("missing")]);
+    assert_eq!(Macros::new().expand(&undefined), Err("macro missing is not defined".to_string()));
+    let unnamed = Script::from(vec![Index(1), Invoke]);
+    assert_eq!(Macros::new().expand(&unnamed), Err("INVOKE at 1 must be followed by a name".to_string()));
+    let in_body = Script::from(vec![Define, name("one"), Invoke, Index(1), EndDefine]);
+    assert_eq!(Macros::new().expand(&in_body), Err("INVOKE in macro one must be followed by a name".to_string()));
+
+    // running an invocation without expanding it fails
+    let mut machine = Machine::from(undefined);
+    assert!(machine.execute(&NullIO).is_none());
+    assert_eq!(machine.pop(), Some(Error("INVOKE must be expanded before running".to_string())));
+}
+
+#[test]
+pub fn unexpanded() {
+    let script = Script::from(vec![
+        Define, name("one"), Index(1), EndDefine,
+        Invoke, name("one")
+    ]);
+
+    // the checker rejects scripts with definitions in them
+    assert!(check(&script).is_err());
+
+    // running a definition without expanding it fails
+    let mut machine = Machine::from(script);
+    assert!(machine.execute(&NullIO).is_none());
+    match machine.pop() {
+        Some(Error(e)) => assert_eq!(e, "DEFINE must be expanded before running"),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn macros_ser_0() {
+    let script = Script::from(vec![
+        Define, name("square"), Dup, Mul, EndDefine,
+        Index(3),
+        Invoke, name("square")
+    ]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""DEFINE square DUP MUL ENDDEF 3 INVOKE square""#);
+}
+
+#[test]
+pub fn macros_de_0() {
+    let s1 = Script::from(vec![
+        Define, name("square"), Dup, Mul, EndDefine,
+        Index(3),
+        Invoke, name("square")
+    ]);
+    let s = r#""DEFINE square DUP MUL ENDDEF 3 INVOKE square""#;
+    let s2: Script<CCLang> = serde_json::from_str(s).unwrap();
+    assert_eq!(s1, s2);
+}
@@ -0,0 +1,255 @@
+#[cfg(feature = "hash-sha2")]
+use bytes::Bytes;
+use cclang::{
+    CCLang::{
+        Decode,
+        EncodingId,
+        HashingId,
+        Index,
+        MerkleRoot,
+        MerkleVerify,
+        Text
+    },
+    Encoding,
+    Hashing,
+    Script
+};
+#[cfg(feature = "hash-sha2")]
+use cclang::{
+    merkle,
+    CCLang::{
+        self,
+        Binary,
+        Boolean,
+        Equal,
+        Error
+    },
+    Machine,
+    NullIO
+};
+
+/* TEST DATA (from the certificate transparency test suite):
+leaves: "", 00, 10, 2021, 3031, 40414243, 5051525354555657, 606162636465666768696a6b6c6d6e6f
+
+SHA256 roots for the first n leaves:
+0: e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855
+1: 6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d
+2: fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125
+3: aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77
+4: d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7
+5: 4e3bbb1f7b478dcfe71fb631631519a3bca12c9aefca1612bfce4c13a86264d4
+6: 76e67dadbcdf1e10e1b74ddc608abd2f98dfb16fbce75277b5232a127f2087ef
+7: ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c
+8: 5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328
+*/
+
+#[cfg(feature = "hash-sha2")]
+const LEAVES: [&str; 8] = [
+    "",
+    "00",
+    "10",
+    "2021",
+    "3031",
+    "40414243",
+    "5051525354555657",
+    "606162636465666768696a6b6c6d6e6f"
+];
+
+const ROOTS: [&str; 9] = [
+    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+    "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+    "aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77",
+    "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+    "4e3bbb1f7b478dcfe71fb631631519a3bca12c9aefca1612bfce4c13a86264d4",
+    "76e67dadbcdf1e10e1b74ddc608abd2f98dfb16fbce75277b5232a127f2087ef",
+    "ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c",
+    "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328"
+];
+
+#[cfg(feature = "hash-sha2")]
+fn leaves(n: usize) -> Vec<Bytes> {
+    LEAVES[..n].iter().map(|l| Bytes::from(hex::decode(l).unwrap())).collect()
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn merkle_roots() {
+    for n in 0..=8 {
+        let root = merkle::root(Hashing::SHA256, &leaves(n));
+        assert_eq!(hex::encode(root.as_ref()), ROOTS[n]);
+    }
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn merkle_root_errors() {
+    let fails = |script: Vec<CCLang>| {
+        let mut machine = Machine::from(Script::from(script));
+        assert!(machine.execute(&NullIO).is_none());
+        machine.pop()
+    };
+    let leaf = || Binary(Bytes::from(&b"leaf"[..]));
+
+    // the count is checked against the stack before anything is allocated
+    assert_eq!(fails(vec![Index(1099511627776), HashingId(Hashing::SHA256), MerkleRoot]),
+               Some(Error("stack underflow".to_string())));
+    assert_eq!(fails(vec![leaf(), Index(-1), HashingId(Hashing::SHA256), MerkleRoot]),
+               Some(Error("item count must not be negative".to_string())));
+    assert_eq!(fails(vec![leaf(), Index(1), Index(2), HashingId(Hashing::SHA256), MerkleRoot]),
+               Some(Error("leaves must be Binary".to_string())));
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn merkle_proofs() {
+    // every leaf in every tree size verifies against its root and a tampered
+    // proof does not
+    for n in 1..=8 {
+        let l = leaves(n);
+        let root = hex::decode(ROOTS[n]).unwrap();
+        for i in 0..n {
+            let mut proof: Vec<u8> = merkle::proof(Hashing::SHA256, &l, i).unwrap().concat();
+            assert!(merkle::verify(Hashing::SHA256, &l[i], &proof, i, n, &root));
+            if n > 1 {
+                proof[0] ^= 1;
+                assert!(!merkle::verify(Hashing::SHA256, &l[i], &proof, i, n, &root));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn merkle_root_0() {
+    let mut script: Vec<CCLang> = leaves(7).into_iter().map(Binary).collect();
+    script.append(&mut vec![
+        Index(7),
+        HashingId(Hashing::SHA256),
+        MerkleRoot,
+
+        // decode and push the expected root
+        Text(ROOTS[7].to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+
+        // pop the calculated root and the expected root and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(Script::from(script));
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn merkle_verify_0() {
+    let l = leaves(7);
+    let proof = merkle::proof(Hashing::SHA256, &l, 6).unwrap().concat();
+
+    let script = Script::from(vec![
+        // push the leaf and its inclusion proof
+        Binary(l[6].clone()),
+        Binary(Bytes::from(proof)),
+
+        // the leaf is at index 6 in a tree of 7 leaves
+        Index(6),
+        Index(7),
+
+        // decode and push the signed root
+        Text(ROOTS[7].to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+
+        HashingId(Hashing::SHA256),
+        MerkleVerify
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn merkle_verify_1() {
+    let l = leaves(7);
+    let proof = merkle::proof(Hashing::SHA256, &l, 6).unwrap().concat();
+
+    let script = Script::from(vec![
+        // push a leaf that isn't at index 6 with the proof for index 6
+        Binary(l[5].clone()),
+        Binary(Bytes::from(proof)),
+        Index(6),
+        Index(7),
+        Text(ROOTS[7].to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+        HashingId(Hashing::SHA256),
+        MerkleVerify
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value false
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, false),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn merkle_ser_0() {
+    let script = Script::from(vec![
+        Index(7),
+        HashingId(Hashing::SHA256),
+        MerkleRoot,
+        Index(6),
+        Index(7),
+        Text(ROOTS[7].to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+        HashingId(Hashing::SHA256),
+        MerkleVerify
+    ]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""7 SHA256 MERKLEROOT 6 7 ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c Hex DECODE SHA256 MERKLEVERIFY""#);
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn merkle_de_0() {
+    let s1 = Script::from(vec![
+        Index(7),
+        HashingId(Hashing::SHA256),
+        MerkleRoot,
+        Index(6),
+        Index(7),
+        Text(ROOTS[7].to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+        HashingId(Hashing::SHA256),
+        MerkleVerify
+    ]);
+    let s = r#""7 SHA256 MERKLEROOT 6 7 ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c Hex DECODE SHA256 MERKLEVERIFY""#;
+    let s2: Script<CCLang> = serde_json::from_str(s).unwrap();
+    assert_eq!(s1, s2);
+}
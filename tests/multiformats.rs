@@ -0,0 +1,282 @@
+use bytes::{
+    BufMut,
+    BytesMut
+};
+use cclang::{
+    CCLang::{
+        self,
+        Binary,
+        Decode,
+        EncodingId,
+        Error,
+        FromMultihash,
+        HashingId,
+        MultiKey,
+        MultihashVerify,
+        Text,
+        ToMultihash
+    },
+    Encoding,
+    Hashing,
+    Machine,
+    NullIO,
+    Script
+};
+#[cfg(feature = "hash-sha2")]
+use cclang::CCLang::{
+    Boolean,
+    Equal,
+    Hash
+};
+#[cfg(feature = "sign-ed25519")]
+use cclang::{
+    CCLang::{
+        Secret,
+        SigningId
+    },
+    Signing
+};
+
+/* TEST DATA:
+did:key (from the did:key spec):
+did: did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK
+ mc: ed012e6fcce36701dc791488e0d0b1745cc1e33a4c1c9fcc41c63bd343dbbe0970e6
+ pk: 2e6fcce36701dc791488e0d0b1745cc1e33a4c1c9fcc41c63bd343dbbe0970e6
+
+ed25519-priv (seed and public key from RFC 8410):
+  mc: 8026d4ee72dbf913584ad5b6d8f1f769f8ad3afe7c28cbf1d4fbe097a88f44755842
+  pk: 19bf44096984cdfe8541bac167dc3b96c85086aa30b6b6cb0c5c38ad703166e1
+
+multihash:
+data: "hello world"
+  mh: 1220b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9
+*/
+
+#[cfg(feature = "hash-sha2")]
+const HELLO_WORLD: &str = "68656c6c6f20776f726c64";
+#[cfg(feature = "hash-sha2")]
+const HELLO_WORLD_MH: &str = "1220b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+
+fn binary(h: &str) -> CCLang {
+    let mut b = BytesMut::new();
+    b.put_slice(&hex::decode(h.to_string()).unwrap());
+    Binary(b.freeze())
+}
+
+#[cfg(all(feature = "encoding-base58", feature = "sign-ed25519"))]
+#[test]
+pub fn multikey_did_key() {
+    let script = Script::from(vec![
+        // the multibase part of the did:key
+        Text("z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK".to_string()),
+        EncodingId(Encoding::Multibase),
+        Decode,
+        MultiKey
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should be the raw key and the signing algorithm id
+    assert_eq!(result.size(), 2 as usize);
+    assert_eq!(result.pop(), Some(SigningId(Signing::Ed25519)));
+    assert_eq!(result.pop(), Some(binary("2e6fcce36701dc791488e0d0b1745cc1e33a4c1c9fcc41c63bd343dbbe0970e6")));
+}
+
+#[cfg(feature = "sign-ed25519")]
+#[test]
+pub fn multikey_private() {
+    let script = Script::from(vec![
+        binary("8026d4ee72dbf913584ad5b6d8f1f769f8ad3afe7c28cbf1d4fbe097a88f44755842"),
+        MultiKey
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // the private key is pushed as a Secret holding the seed and public key
+    assert_eq!(result.pop(), Some(SigningId(Signing::Ed25519)));
+    match result.pop() {
+        Some(Secret(sk)) => {
+            assert_eq!(sk.len(), 64 as usize);
+            assert_eq!(&sk.as_ref()[32..], &hex::decode("19bf44096984cdfe8541bac167dc3b96c85086aa30b6b6cb0c5c38ad703166e1".to_string()).unwrap()[..]);
+        },
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn multikey_unsupported() {
+    let script = Script::from(vec![
+        // x25519-pub
+        binary("ec012e6fcce36701dc791488e0d0b1745cc1e33a4c1c9fcc41c63bd343dbbe0970e6"),
+        MultiKey
+    ]);
+    let mut machine = Machine::from(script);
+    assert!(machine.execute(&NullIO).is_none());
+    match machine.pop() {
+        Some(Error(e)) => assert_eq!(e, "unsupported multicodec key"),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn tomultihash() {
+    let script = Script::from(vec![
+        // push the expected multihash
+        binary(HELLO_WORLD_MH),
+
+        // hash the data and wrap the digest
+        binary(HELLO_WORLD),
+        HashingId(Hashing::SHA256),
+        Hash,
+        HashingId(Hashing::SHA256),
+        ToMultihash,
+
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn frommultihash() {
+    let script = Script::from(vec![
+        binary(HELLO_WORLD_MH),
+        FromMultihash
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should be the digest and the hashing algorithm id
+    assert_eq!(result.size(), 2 as usize);
+    assert_eq!(result.pop(), Some(HashingId(Hashing::SHA256)));
+    assert_eq!(result.pop(), Some(binary(&HELLO_WORLD_MH[4..])));
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn multihash_verify() {
+    let script = Script::from(vec![
+        binary(HELLO_WORLD),
+        binary(HELLO_WORLD_MH),
+        MultihashVerify
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+    assert_eq!(result.size(), 1 as usize);
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn multihash_verify_truncated() {
+    let script = Script::from(vec![
+        binary(HELLO_WORLD),
+        // the first 16 bytes of the digest
+        binary("1210b94d27b9934d3e08a52e52d7da7dabfa"),
+        MultihashVerify
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn multihash_verify_too_short() {
+    let script = Script::from(vec![
+        binary(HELLO_WORLD),
+        // the first 15 bytes of the digest
+        binary("120fb94d27b9934d3e08a52e52d7da7dab"),
+        MultihashVerify
+    ]);
+    let mut machine = Machine::from(script);
+    assert!(machine.execute(&NullIO).is_none());
+    match machine.pop() {
+        Some(Error(e)) => assert_eq!(e, "multihash digest is too short to verify"),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn multihash_verify_wrong_data() {
+    let script = Script::from(vec![
+        binary("68656c6c6f20776f726c65"),
+        binary(HELLO_WORLD_MH),
+        MultihashVerify
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, false),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn multihash_bad_length() {
+    let script = Script::from(vec![
+        binary(HELLO_WORLD),
+        // the length says 33 bytes but there are only 32
+        binary("1221b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"),
+        MultihashVerify
+    ]);
+    let mut machine = Machine::from(script);
+    assert!(machine.execute(&NullIO).is_none());
+    match machine.pop() {
+        Some(Error(e)) => assert_eq!(e, "multihash length does not match the digest"),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn multihash_ser_0() {
+    let script = Script::from(vec![
+        Text("z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK".to_string()),
+        EncodingId(Encoding::Multibase),
+        Decode,
+        MultiKey,
+        HashingId(Hashing::SHA256),
+        ToMultihash,
+        FromMultihash,
+        MultihashVerify
+    ]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK Multibase DECODE MULTIKEY SHA256 TOMULTIHASH FROMMULTIHASH MULTIHASHVERIFY""#);
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn multihash_de_0() {
+    let s1 = Script::from(vec![
+        Text("z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK".to_string()),
+        EncodingId(Encoding::Multibase),
+        Decode,
+        MultiKey,
+        HashingId(Hashing::SHA256),
+        ToMultihash,
+        FromMultihash,
+        MultihashVerify
+    ]);
+    let s = r#""z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK Multibase DECODE MULTIKEY SHA256 TOMULTIHASH FROMMULTIHASH MULTIHASHVERIFY""#;
+    let s2: Script<CCLang> = serde_json::from_str(s).unwrap();
+    assert_eq!(s1, s2);
+}
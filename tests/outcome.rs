@@ -0,0 +1,157 @@
+use cclang::{
+    check::check,
+    outcome::run,
+    CCLang::{
+        self,
+        Add,
+        Assert,
+        Boolean,
+        Decode,
+        Else,
+        EncodingId,
+        Equal,
+        Fi,
+        Halt,
+        If,
+        Index,
+        Loop,
+        Pick,
+        Repeat,
+        Return,
+        Text,
+        ToText
+    },
+    Encoding,
+    Machine,
+    NullIO,
+    Script,
+    ScriptOutcome
+};
+
+fn outcome(script: Vec<CCLang>, clean_stack: bool) -> ScriptOutcome {
+    let mut machine = Machine::from(Script::from(script));
+    run(&mut machine, &NullIO, clean_stack)
+}
+
+#[test]
+pub fn success() {
+    let script = vec![Index(1), Index(1), Equal];
+    assert_eq!(outcome(script.clone(), false), ScriptOutcome::Success);
+    assert_eq!(outcome(script, true), ScriptOutcome::Success);
+}
+
+#[test]
+pub fn failure() {
+    assert_eq!(outcome(vec![Index(1), Index(2), Equal], false), ScriptOutcome::Failure);
+
+    // only a boolean true is a success
+    assert_eq!(outcome(vec![Index(1)], false), ScriptOutcome::Failure);
+    assert_eq!(outcome(vec![], false), ScriptOutcome::Failure);
+}
+
+#[test]
+pub fn error() {
+    // 0xff is not valid UTF-8
+    let script = vec![
+        Text("ff".to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+        ToText
+    ];
+    match outcome(script, false) {
+        ScriptOutcome::Error(e) => assert!(e.starts_with("binary is not valid UTF-8")),
+        o => panic!("unexpected outcome {:?}", o)
+    }
+}
+
+#[test]
+pub fn wrong_type() {
+    // PICK panics on a count that isn't an Index
+    let script = vec![Index(5), Text("x".to_string()), Pick];
+    match outcome(script, false) {
+        ScriptOutcome::Error(e) => assert!(e.starts_with("the script panicked")),
+        o => panic!("unexpected outcome {:?}", o)
+    }
+}
+
+#[test]
+pub fn clean_stack() {
+    // junk left under the result
+    let script = vec![Index(7), Boolean(true)];
+    assert_eq!(outcome(script.clone(), false), ScriptOutcome::Success);
+    assert_eq!(outcome(script, true), ScriptOutcome::Failure);
+}
+
+#[test]
+pub fn assert_false() {
+    let script = vec![Boolean(false), Assert, Boolean(true)];
+    assert_eq!(outcome(script, false), ScriptOutcome::Failure);
+}
+
+#[test]
+pub fn return_early() {
+    let script = vec![
+        Boolean(true),
+        Boolean(true),
+        If,
+            Return,
+        Fi,
+        Boolean(false)
+    ];
+    assert!(outcome(script, true).is_success());
+}
+
+#[test]
+pub fn return_from_loop() {
+    let mut machine = Machine::from(Script::from(vec![
+        Index(0),
+        Index(5),
+        Repeat,
+            Index(1),
+            Add,
+            Return,
+        Loop
+    ]));
+    let mut result = machine.execute(&NullIO).unwrap();
+    assert_eq!(result.pop(), Some(Index(1)));
+}
+
+#[test]
+pub fn halt() {
+    let script = vec![
+        Boolean(true),
+        Boolean(false),
+        If,
+            Boolean(true),
+        Else,
+            Halt,
+        Fi
+    ];
+    assert_eq!(outcome(script.clone(), false), ScriptOutcome::Failure);
+
+    // halt leaves FALSE on top
+    let mut machine = Machine::from(Script::from(script));
+    assert!(machine.execute(&NullIO).is_none());
+    assert_eq!(machine.pop(), Some(Boolean(false)));
+}
+
+#[test]
+pub fn check_return() {
+    let script = Script::from(vec![Boolean(true), If, Return, Else, Halt, Fi]);
+    assert_eq!(check(&script).unwrap().cost, 4);
+}
+
+#[test]
+pub fn outcome_ser_0() {
+    let script = Script::from(vec![Boolean(true), If, Boolean(true), Return, Fi, Halt]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""TRUE IF TRUE RETURN FI HALT""#);
+}
+
+#[test]
+pub fn outcome_de_0() {
+    let s1 = Script::from(vec![Boolean(true), If, Boolean(true), Return, Fi, Halt]);
+    let s = r#""TRUE IF TRUE RETURN FI HALT""#;
+    let s2: Script<CCLang> = serde_json::from_str(s).unwrap();
+    assert_eq!(s1, s2);
+}
@@ -0,0 +1,182 @@
+use bytes::Bytes;
+use cclang::{
+    check::check_with,
+    outcome::run_with,
+    CCLang::{
+        self,
+        Add,
+        Binary,
+        Decode,
+        EncodingId,
+        Equal,
+        Error,
+        Index,
+        Open,
+        Sub,
+        Text
+    },
+    Context,
+    Encoding,
+    ExecutionPolicy,
+    Machine,
+    NullIO,
+    Script,
+    ScriptOutcome
+};
+#[cfg(feature = "hash-sha2")]
+use cclang::{
+    canonical::encode,
+    check::check,
+    CCLang::{
+        CallHash,
+        FromMultihash,
+        GetVar,
+        Hash,
+        HashingId,
+        List,
+        Pop
+    },
+    Hashing,
+    ScriptId
+};
+
+#[cfg(feature = "hash-sha2")]
+const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+fn binary(b: &[u8]) -> CCLang {
+    Binary(Bytes::copy_from_slice(b))
+}
+
+// run the script with the policy, returning the error it stopped with
+fn refused(script: Script<CCLang>, policy: ExecutionPolicy) -> Option<CCLang> {
+    let mut ctx = Context::new();
+    ctx.policy(policy);
+    let mut machine = Machine::from(script);
+    assert!(ctx.execute(&mut machine, &NullIO).is_none());
+    machine.pop()
+}
+
+#[test]
+pub fn verify_only_refuses_io() {
+    let script = Script::from(vec![Text("secrets.txt".to_string()), Open]);
+    let policy = ExecutionPolicy::verify_only();
+    assert_eq!(check_with(&script, &policy), Err("OPEN at 1 is not allowed by the execution policy".to_string()));
+    assert_eq!(refused(script, policy), Some(Error("OPEN is not allowed by the execution policy".to_string())));
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn verify_only_allows_hashing() {
+    let script = Script::from(vec![
+        binary(b"abc"),
+        HashingId(Hashing::SHA256),
+        Hash,
+        binary(&hex::decode(ABC_SHA256).unwrap()),
+        Equal
+    ]);
+    let policy = ExecutionPolicy::verify_only();
+    assert_eq!(check_with(&script, &policy), check(&script));
+    let mut ctx = Context::new();
+    ctx.policy(policy);
+    let mut machine = Machine::from(script);
+    assert_eq!(run_with(&mut machine, &NullIO, true, &ctx), ScriptOutcome::Success);
+}
+
+#[test]
+pub fn allow_opcodes() {
+    let mut policy = ExecutionPolicy::new();
+    policy.allow_opcodes(&[Add, Equal]);
+
+    // data is always allowed
+    let script = Script::from(vec![Index(2), Index(3), Add, Index(5), Equal]);
+    assert!(check_with(&script, &policy).is_ok());
+    let mut ctx = Context::new();
+    ctx.policy(policy.clone());
+    let mut machine = Machine::from(script);
+    assert_eq!(run_with(&mut machine, &NullIO, true, &ctx), ScriptOutcome::Success);
+
+    let script = Script::from(vec![Index(7), Index(2), Sub, Index(5), Equal]);
+    assert_eq!(check_with(&script, &policy), Err("SUB at 2 is not allowed by the execution policy".to_string()));
+    assert_eq!(refused(script, policy), Some(Error("SUB is not allowed by the execution policy".to_string())));
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn allow_hashing() {
+    let mut policy = ExecutionPolicy::new();
+    policy.allow_hashing(&[Hashing::SHA512]);
+    let script = Script::from(vec![binary(b"abc"), HashingId(Hashing::SHA256), Hash]);
+    assert_eq!(check_with(&script, &policy), Err("SHA256 at 1 is not allowed by the execution policy".to_string()));
+    assert_eq!(refused(script, policy), Some(Error("SHA256 is not allowed by the execution policy".to_string())));
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn getvar_id() {
+    // an id bound as an input is checked where it is used
+    let script = Script::from(vec![binary(b"abc"), Text("h".to_string()), GetVar, Hash]);
+    let mut policy = ExecutionPolicy::new();
+    policy.allow_hashing(&[Hashing::SHA512]);
+    let mut ctx = Context::new();
+    ctx.policy(policy).bind("h", HashingId(Hashing::SHA256));
+    let mut machine = Machine::from(script);
+    assert!(ctx.execute(&mut machine, &NullIO).is_none());
+    assert_eq!(machine.pop(), Some(Error("SHA256 is not allowed by the execution policy".to_string())));
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn deny_in_list() {
+    let mut policy = ExecutionPolicy::new();
+    policy.deny(&[HashingId(Hashing::SHA256)]);
+    let script = Script::from(vec![List(vec![Index(1), HashingId(Hashing::SHA256)]), Pop]);
+    assert!(check_with(&script, &policy).unwrap_err().ends_with("at 0 is not allowed by the execution policy"));
+    assert!(check_with(&script, &ExecutionPolicy::new()).is_ok());
+}
+
+#[test]
+pub fn multibase() {
+    // allowing multibase doesn't allow the base its prefix names
+    let script = Script::from(vec![Text("f68656c6c6f".to_string()), EncodingId(Encoding::Multibase), Decode]);
+    let mut policy = ExecutionPolicy::new();
+    policy.allow_encodings(&[Encoding::Multibase]);
+    assert_eq!(refused(script.clone(), policy.clone()), Some(Error("Hex is not allowed by the execution policy".to_string())));
+
+    policy.allow_encodings(&[Encoding::Hex]);
+    let mut ctx = Context::new();
+    ctx.policy(policy);
+    let mut machine = Machine::from(script);
+    let mut result = ctx.execute(&mut machine, &NullIO).unwrap();
+    assert_eq!(result.pop(), Some(binary(b"hello")));
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn frommultihash() {
+    // the algorithm named in a multihash is refused when it is unwrapped
+    let mut mh = vec![0x12, 0x20];
+    mh.extend_from_slice(&hex::decode(ABC_SHA256).unwrap());
+    let script = Script::from(vec![binary(&mh), FromMultihash]);
+    let mut policy = ExecutionPolicy::new();
+    policy.allow_hashing(&[Hashing::SHA512]);
+    assert!(check_with(&script, &policy).is_ok());
+    assert_eq!(refused(script, policy), Some(Error("SHA256 is not allowed by the execution policy".to_string())));
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn callhash() {
+    // the called script is checked against the same policy
+    let called = Script::from(vec![Index(2), Add, Index(5), Equal]);
+    let script = Script::from(vec![
+        Index(3),
+        Binary(encode(&called).unwrap()),
+        Binary(called.id(Hashing::SHA256).unwrap()),
+        HashingId(Hashing::SHA256),
+        CallHash
+    ]);
+    let mut policy = ExecutionPolicy::new();
+    policy.deny(&[Add]);
+    assert_eq!(check_with(&script, &policy), Err("CALLHASH at 4: ADD at 1 is not allowed by the execution policy".to_string()));
+    assert_eq!(refused(script, policy), Some(Error("ADD is not allowed by the execution policy".to_string())));
+}
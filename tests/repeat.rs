@@ -0,0 +1,239 @@
+use cclang::{
+    check::check,
+    CCLang::{
+        self,
+        Add,
+        Boolean,
+        Dup,
+        Else,
+        Loop,
+        Error,
+        Fi,
+        If,
+        Index,
+        Not,
+        Repeat,
+        Swap
+    },
+    Machine,
+    NullIO,
+    Script
+};
+
+// run the script and return the stack from the bottom to the top
+fn run(script: Vec<CCLang>) -> Vec<CCLang> {
+    let mut machine = Machine::from(Script::from(script));
+    let mut result = machine.execute(&NullIO).unwrap();
+    let mut items = Vec::new();
+    while let Some(i) = result.pop() {
+        items.insert(0, i);
+    }
+    items
+}
+
+#[test]
+pub fn repeat() {
+    let result = run(vec![
+        Index(0),
+        Index(3),
+        Repeat,
+            Index(2),
+            Add,
+        Loop
+    ]);
+    assert_eq!(result, vec![Index(6)]);
+}
+
+#[test]
+pub fn repeat_zero() {
+    let result = run(vec![
+        Index(1),
+        Index(0),
+        Repeat,
+            Index(2),
+            Add,
+        Loop,
+        Index(5)
+    ]);
+    assert_eq!(result, vec![Index(1), Index(5)]);
+}
+
+#[test]
+pub fn repeat_nested() {
+    let result = run(vec![
+        Index(0),
+        Index(3),
+        Repeat,
+            Index(4),
+            Repeat,
+                Index(1),
+                Add,
+            Loop,
+        Loop
+    ]);
+    assert_eq!(result, vec![Index(12)]);
+}
+
+#[test]
+pub fn repeat_if() {
+    // alternate between adding 1 and adding 10
+    let result = run(vec![
+        Index(0),
+        Boolean(true),
+        Index(4),
+        Repeat,
+            Dup,
+            If,
+                Swap,
+                Index(1),
+                Add,
+                Swap,
+            Else,
+                Swap,
+                Index(10),
+                Add,
+                Swap,
+            Fi,
+            Not,
+        Loop
+    ]);
+    assert_eq!(result, vec![Index(22), Boolean(true)]);
+}
+
+#[test]
+pub fn if_repeat() {
+    let result = run(vec![
+        Index(0),
+        Boolean(false),
+        If,
+            Index(2),
+            Repeat,
+                Index(1),
+                Add,
+            Loop,
+        Else,
+            Index(3),
+            Repeat,
+                Index(2),
+                Add,
+            Loop,
+        Fi
+    ]);
+    assert_eq!(result, vec![Index(6)]);
+}
+
+#[test]
+pub fn repeat_computed_count() {
+    // the count is computed so it isn't known before running
+    let script = Script::from(vec![
+        Index(0),
+        Index(1),
+        Index(2),
+        Add,
+        Repeat,
+            Index(2),
+            Add,
+        Loop
+    ]);
+    assert!(check(&script).is_err());
+
+    let mut machine = Machine::from(script);
+    assert!(machine.execute(&NullIO).is_none());
+    match machine.pop() {
+        Some(Error(e)) => assert_eq!(e, "REPEAT count must be a literal Index"),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn check_cost() {
+    // five instructions
+    let script = Script::from(vec![Index(1), Index(2), Add, Index(3), Add]);
+    assert_eq!(check(&script).unwrap().cost, 5);
+
+    // the literal, the 'REPEAT' and 3 iterations of the body and the 'END'
+    let script = Script::from(vec![
+        Index(0),
+        Index(3),
+        Repeat,
+            Index(2),
+            Add,
+        Loop
+    ]);
+    assert_eq!(check(&script).unwrap().cost, 2 + 1 + 3 * 3);
+
+    // the 'IF', the longest branch and the 'ELSE' or 'FI'
+    let script = Script::from(vec![
+        Boolean(true),
+        If,
+            Index(1),
+        Else,
+            Index(2),
+            Index(3),
+            Add,
+        Fi
+    ]);
+    assert_eq!(check(&script).unwrap().cost, 1 + 1 + 3 + 1);
+
+    // the cost matches the instructions actually executed
+    let script = Script::from(vec![
+        Index(0),
+        Index(3),
+        Repeat,
+            Index(4),
+            Repeat,
+                Index(1),
+                Add,
+            Loop,
+        Loop
+    ]);
+    assert_eq!(check(&script).unwrap().cost, 2 + 1 + 3 * (1 + 1 + 4 * 3 + 1));
+}
+
+#[test]
+pub fn check_unbalanced() {
+    assert!(check(&Script::from(vec![Index(1), Repeat, Index(1)])).is_err());
+    assert!(check(&Script::from(vec![Index(1), Loop])).is_err());
+    assert!(check(&Script::from(vec![Boolean(true), If, Index(1)])).is_err());
+    assert!(check(&Script::from(vec![Boolean(true), If, Index(1), Loop])).is_err());
+
+    // overlapping blocks
+    assert!(check(&Script::from(vec![
+        Boolean(true),
+        If,
+            Index(1),
+            Repeat,
+        Fi,
+            Index(1),
+        Loop
+    ])).is_err());
+}
+
+#[test]
+pub fn repeat_ser_0() {
+    let script = Script::from(vec![
+        Index(0),
+        Index(3),
+        Repeat,
+        Index(2),
+        Add,
+        Loop
+    ]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""0 3 REPEAT 2 ADD LOOP""#);
+}
+
+#[test]
+pub fn repeat_de_0() {
+    let s1 = Script::from(vec![
+        Index(0),
+        Index(3),
+        Repeat,
+        Index(2),
+        Add,
+        Loop
+    ]);
+    let s = r#""0 3 REPEAT 2 ADD LOOP""#;
+    let s2: Script<CCLang> = serde_json::from_str(s).unwrap();
+    assert_eq!(s1, s2);
+}
@@ -0,0 +1,253 @@
+use bytes::{
+    BufMut,
+    BytesMut
+};
+use cclang::{
+    check::check_with,
+    CCLang::{
+        self,
+        Binary,
+        Boolean,
+        Decode,
+        Encode,
+        EncodingId,
+        Equal,
+        Error,
+        Index,
+        List,
+        Reveal,
+        Text,
+        ToSecret
+    },
+    Encoding,
+    ExecutionPolicy,
+    Machine,
+    NullIO,
+    Script,
+    Secret
+};
+#[cfg(feature = "enc-xsalsa20")]
+use cclang::{
+    CCLang::{
+        Decrypt,
+        EncryptionId
+    },
+    Encryption
+};
+#[cfg(feature = "sign-ed25519")]
+use cclang::{
+    CCLang::{
+        Sign,
+        SigningId
+    },
+    Signing
+};
+
+/* TEST DATA
+ * msg: 7ccf1a3dd89255b11007df39110fa0e83b95030bf3b8b9113d3e0117a24770bc0bf4e61f780e949df0924ade33380dd000b42f394b9e7c0d3191d977df99e83f
+ *  sk: d2acb699a7e41806bdb3d4400a6ace771e5e6e079117fa941255014ea433e7b02eb9136429881b23cfdb02fba18422e2467ba0fa78527cf2d96c0791b2827a10
+ * sig: df087999d4d9d01f97de110daf50dca0f422ebe624d20196820a0a97e49314c366dede0f4a3d869872c4d841910b14460a4c47fbb513f2bf82a7de9fc746a70b
+ *
+ * wrapped key: 7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7
+ * nonce: a65af86b4856df7f655ff71132af566a736b91e24a11e114
+ * plaintext: 455a8ecfd265c6e4ce63e590679a6e68b1e34b3112cdfe3e655fa47c545ae3f4f13bc066d289ec1d59eda208578d0040ad69d37411ae044583ca2c844ebcc099
+ * box: 64a5fa3599adffef7ca387345760900d1fdb95b74b572b4ac42150f29f11105f7258e5bc135427e9f3c9b1340882de656a4fe7d789e85f9c0b9156ea8bc28692f29d0ba4991fed9daf956d174f75e058
+ */
+
+#[test]
+pub fn secret_decode() {
+    let script = Script::from(vec![
+        // mark the encoded key as secret and decode it into a Secret
+        Text("d2acb699a7e41806bdb3d4400a6ace771e5e6e079117fa941255014ea433e7b02eb9136429881b23cfdb02fba18422e2467ba0fa78527cf2d96c0791b2827a10".to_string()),
+        ToSecret,
+        EncodingId(Encoding::Hex),
+        Decode
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a redacted secret
+    match result.pop() {
+        Some(CCLang::Secret(s)) => {
+            assert_eq!(s.len(), 64 as usize);
+            assert_eq!(format!("{:?}", CCLang::Secret(s)), "Secret(REDACTED)");
+        },
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "sign-ed25519")]
+#[test]
+pub fn secret_sign() {
+    let script = Script::from(vec![
+        // decode and push the expected signature
+        Text("df087999d4d9d01f97de110daf50dca0f422ebe624d20196820a0a97e49314c366dede0f4a3d869872c4d841910b14460a4c47fbb513f2bf82a7de9fc746a70b".to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+
+        // decode and push the message to be sign
+        Text("7ccf1a3dd89255b11007df39110fa0e83b95030bf3b8b9113d3e0117a24770bc0bf4e61f780e949df0924ade33380dd000b42f394b9e7c0d3191d977df99e83f".to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+
+        // decode the secret key into a Secret
+        Text("d2acb699a7e41806bdb3d4400a6ace771e5e6e079117fa941255014ea433e7b02eb9136429881b23cfdb02fba18422e2467ba0fa78527cf2d96c0791b2827a10".to_string()),
+        ToSecret,
+        EncodingId(Encoding::Hex),
+        Decode,
+
+        // sign with the Secret and compare with the expected signature
+        SigningId(Signing::Ed25519),
+        Sign,
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "enc-xsalsa20")]
+#[test]
+pub fn secret_decrypt() {
+    let script = Script::from(vec![
+        // decode and push the ciphertext
+        Text("64a5fa3599adffef7ca387345760900d1fdb95b74b572b4ac42150f29f11105f7258e5bc135427e9f3c9b1340882de656a4fe7d789e85f9c0b9156ea8bc28692f29d0ba4991fed9daf956d174f75e058".to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+
+        // decode the wrapping key into a Secret
+        Text("7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7".to_string()),
+        ToSecret,
+        EncodingId(Encoding::Hex),
+        Decode,
+
+        // decode and push the nonce
+        Text("a65af86b4856df7f655ff71132af566a736b91e24a11e114".to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+
+        // unwrapping with a Secret key produces a Secret
+        EncryptionId(Encryption::XSalsa20Poly1305),
+        Decrypt
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be the unwrapped secret
+    let plaintext = hex::decode("455a8ecfd265c6e4ce63e590679a6e68b1e34b3112cdfe3e655fa47c545ae3f4f13bc066d289ec1d59eda208578d0040ad69d37411ae044583ca2c844ebcc099".to_string()).unwrap();
+    match result.pop() {
+        Some(CCLang::Secret(s)) => assert_eq!(s, Secret::from(plaintext)),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn secret_reveal() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected binary
+        Binary(b.freeze()),
+
+        // decode into a Secret and then explicitly reveal it
+        Text("7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7".to_string()),
+        ToSecret,
+        EncodingId(Encoding::Hex),
+        Decode,
+        Reveal,
+
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn secret_encode() {
+    let script = Script::from(vec![
+        // a Secret can't be encoded without first revealing it
+        Text("7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7".to_string()),
+        ToSecret,
+        EncodingId(Encoding::Hex),
+        Decode,
+        EncodingId(Encoding::Hex),
+        Encode
+    ]);
+    let mut machine = Machine::from(script);
+    assert!(machine.execute(&NullIO).is_none());
+    assert_eq!(machine.pop(), Some(Error("cannot encode a Secret without REVEAL".to_string())));
+}
+
+#[test]
+pub fn secret_serialize() {
+    // a script holding a Secret is shown without the bytes, and that text
+    // can't be parsed back into a script
+    let script = Script::from(vec![CCLang::Secret(Secret::from(vec![1, 2, 3]))]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""SECRET(REDACTED)""#);
+    let parsed = serde_json::from_str::<Script<CCLang>>(&s);
+    assert!(parsed.unwrap_err().to_string().contains("a Secret has no text form"));
+}
+
+#[test]
+pub fn secret_in_list() {
+    // a list holding a Secret can be shown in an error
+    let list = List(vec![Index(1), CCLang::Secret(Secret::from(vec![1, 2, 3]))]);
+    assert_eq!(list.to_string(), "1 SECRET(REDACTED) 2 PACK");
+    let mut policy = ExecutionPolicy::new();
+    policy.deny(&[Index(1)]);
+    let script = Script::from(vec![list]);
+    assert_eq!(check_with(&script, &policy), Err("1 SECRET(REDACTED) 2 PACK at 0 is not allowed by the execution policy".to_string()));
+}
+
+#[test]
+pub fn secret_ser_0() {
+    let script = Script::from(vec![
+        Text("7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7".to_string()),
+        ToSecret,
+        EncodingId(Encoding::Hex),
+        Decode,
+        Reveal
+    ]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7 SECRET Hex DECODE REVEAL""#);
+}
+
+#[test]
+pub fn secret_de_0() {
+    let s1 = Script::from(vec![
+        Text("7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7".to_string()),
+        ToSecret,
+        EncodingId(Encoding::Hex),
+        Decode,
+        Reveal
+    ]);
+    let s = r#""7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7 SECRET Hex DECODE REVEAL""#;
+    let s2: Script<CCLang> = serde_json::from_str(s).unwrap();
+    assert_eq!(s1, s2);
+}
@@ -0,0 +1,235 @@
+use bytes::{
+    BufMut,
+    Bytes,
+    BytesMut
+};
+use cclang::{
+    shamir,
+    CCLang::{
+        self,
+        Binary,
+        Boolean,
+        Combine,
+        Decode,
+        EncodingId,
+        Equal,
+        Error,
+        Index,
+        Pop,
+        Reveal,
+        Secret,
+        Split,
+        Text
+    },
+    Encoding,
+    Machine,
+    NullIO,
+    Script
+};
+#[cfg(feature = "enc-xsalsa20")]
+use cclang::{
+    CCLang::{
+        Decrypt,
+        EncryptionId
+    },
+    Encryption
+};
+
+/* TEST DATA:
+msg: 455a8ecfd265c6e4ce63e590679a6e68b1e34b3112cdfe3e655fa47c545ae3f4f13bc066d289ec1d59eda208578d0040ad69d37411ae044583ca2c844ebcc099
+key: 7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7
+nonce: a65af86b4856df7f655ff71132af566a736b91e24a11e114
+box: 64a5fa3599adffef7ca387345760900d1fdb95b74b572b4ac42150f29f11105f7258e5bc135427e9f3c9b1340882de656a4fe7d789e85f9c0b9156ea8bc28692f29d0ba4991fed9daf956d174f75e058
+*/
+
+#[test]
+pub fn split_combine() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected secret
+        Binary(b.freeze()),
+
+        // decode and push the secret
+        Text("7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7".to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+
+        // split the secret into 5 shares with a threshold of 3
+        Index(3),
+        Index(5),
+        Split,
+
+        // throw away shares 5 and 4 and recombine shares 1, 2 and 3
+        Pop,
+        Pop,
+        Index(3),
+        Combine,
+
+        // the recombined secret is a Secret, reveal it to compare
+        Reveal,
+
+        // pop the recombined secret and the expected secret and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn split_shares() {
+    let secret = hex::decode("7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7".to_string()).unwrap();
+    let shares = shamir::split(&secret, 3, 5).unwrap();
+    assert_eq!(shares.len(), 5 as usize);
+
+    // every share carries its index followed by one byte per secret byte
+    for (i, share) in shares.iter().enumerate() {
+        assert_eq!(share.len(), secret.len() + 1);
+        assert_eq!(share[0] as usize, i + 1);
+    }
+
+    // any 3 shares recombine the secret
+    let subset = vec![shares[4].clone(), shares[0].clone(), shares[2].clone()];
+    assert_eq!(shamir::combine(&subset).unwrap().as_ref(), &secret[..]);
+
+    // 2 shares do not
+    let subset = vec![shares[1].clone(), shares[3].clone()];
+    assert_ne!(shamir::combine(&subset).unwrap().as_ref(), &secret[..]);
+
+    // duplicate shares are rejected
+    let subset = vec![shares[1].clone(), shares[1].clone(), shares[3].clone()];
+    assert!(shamir::combine(&subset).is_err());
+
+    // the threshold can't be larger than the number of shares
+    assert!(shamir::split(&secret, 6, 5).is_err());
+}
+
+#[test]
+pub fn split_combine_errors() {
+    let fails = |script: Vec<CCLang>| {
+        let mut machine = Machine::from(Script::from(script));
+        assert!(machine.execute(&NullIO).is_none());
+        machine.pop()
+    };
+    let secret = || Binary(Bytes::from(&b"secret"[..]));
+    assert_eq!(fails(vec![secret(), Index(3), Index(2), Split]),
+               Some(Error("threshold must be between 1 and the number of shares".to_string())));
+    assert_eq!(fails(vec![secret(), Index(-1), Index(2), Split]),
+               Some(Error("threshold must be between 1 and the number of shares".to_string())));
+
+    // the count is checked before anything is allocated for it
+    assert_eq!(fails(vec![Index(1099511627776), Combine]), Some(Error("at most 255 shares can be combined".to_string())));
+    assert_eq!(fails(vec![secret(), Index(2), Combine]), Some(Error("stack underflow".to_string())));
+    assert_eq!(fails(vec![Text("share".to_string()), Index(1), Combine]), Some(Error("shares must be Binary".to_string())));
+}
+
+#[test]
+pub fn combine_secret() {
+    let shares = shamir::split(b"secret", 2, 3).unwrap();
+    let script = Script::from(vec![Binary(shares[0].clone()), Binary(shares[1].clone()), Index(2), Combine]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+    assert_eq!(result.pop(), Some(Secret(cclang::Secret::from(&b"secret"[..]))));
+}
+
+#[cfg(feature = "enc-xsalsa20")]
+#[test]
+pub fn combine_decrypt() {
+    let key = hex::decode("7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7".to_string()).unwrap();
+    let shares = shamir::split(&key, 2, 3).unwrap();
+
+    let mut b = BytesMut::new();
+    let data = hex::decode("455a8ecfd265c6e4ce63e590679a6e68b1e34b3112cdfe3e655fa47c545ae3f4f13bc066d289ec1d59eda208578d0040ad69d37411ae044583ca2c844ebcc099".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected plaintext binary
+        Binary(b.freeze()),
+
+        // decode and push the ciphertext
+        Text("64a5fa3599adffef7ca387345760900d1fdb95b74b572b4ac42150f29f11105f7258e5bc135427e9f3c9b1340882de656a4fe7d789e85f9c0b9156ea8bc28692f29d0ba4991fed9daf956d174f75e058".to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+
+        // push two of the three key shares and recombine the key
+        Binary(shares[2].clone()),
+        Binary(shares[0].clone()),
+        Index(2),
+        Combine,
+
+        // decode and push the nonce
+        Text("a65af86b4856df7f655ff71132af566a736b91e24a11e114".to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+
+        // push the encryption algorithm id
+        EncryptionId(Encryption::XSalsa20Poly1305),
+
+        Decrypt,
+
+        // decrypting with a Secret key gives a Secret, reveal it to compare
+        Reveal,
+
+        // pop the decrypted binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn split_ser_0() {
+    let script = Script::from(vec![
+        Text("7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7".to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+        Index(3),
+        Index(5),
+        Split,
+        Pop,
+        Pop,
+        Index(3),
+        Combine
+    ]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7 Hex DECODE 3 5 SPLIT POP POP 3 COMBINE""#);
+}
+
+#[test]
+pub fn split_de_0() {
+    let s1 = Script::from(vec![
+        Text("7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7".to_string()),
+        EncodingId(Encoding::Hex),
+        Decode,
+        Index(3),
+        Index(5),
+        Split,
+        Pop,
+        Pop,
+        Index(3),
+        Combine
+    ]);
+    let s = r#""7e874bde68d5a1f99dc0675c22f4b94705b259b7e6033dc31e598b1f6cc330f7 Hex DECODE 3 5 SPLIT POP POP 3 COMBINE""#;
+    let s2: Script<CCLang> = serde_json::from_str(s).unwrap();
+    assert_eq!(s1, s2);
+}
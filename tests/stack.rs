@@ -2,8 +2,19 @@ use cclang::{
     CCLang::{
         self,
         Boolean,
+        Depth,
+        DropN,
         Dup,
-        Pop
+        Error,
+        Index,
+        Nip,
+        Over,
+        Pick,
+        Pop,
+        Roll,
+        Rot,
+        Swap,
+        Tuck
     },
     Machine,
     NullIO,
@@ -100,3 +111,145 @@ pub fn stack_de_1() {
 }
 
 
+
+// run the script and return the stack from the bottom to the top
+fn run(script: Vec<CCLang>) -> Vec<CCLang> {
+    let mut machine = Machine::from(Script::from(script));
+    let mut result = machine.execute(&NullIO).unwrap();
+    let mut items = Vec::new();
+    while let Some(i) = result.pop() {
+        items.insert(0, i);
+    }
+    items
+}
+
+#[test]
+pub fn swap() {
+    assert_eq!(run(vec![Index(1), Index(2), Swap]), vec![Index(2), Index(1)]);
+}
+
+#[test]
+pub fn over() {
+    assert_eq!(run(vec![Index(1), Index(2), Over]), vec![Index(1), Index(2), Index(1)]);
+}
+
+#[test]
+pub fn rot() {
+    assert_eq!(run(vec![Index(1), Index(2), Index(3), Rot]), vec![Index(2), Index(3), Index(1)]);
+}
+
+#[test]
+pub fn nip() {
+    assert_eq!(run(vec![Index(1), Index(2), Nip]), vec![Index(2)]);
+}
+
+#[test]
+pub fn tuck() {
+    assert_eq!(run(vec![Index(1), Index(2), Tuck]), vec![Index(2), Index(1), Index(2)]);
+}
+
+#[test]
+pub fn pick() {
+    // 0 PICK is DUP and 1 PICK is OVER
+    assert_eq!(run(vec![Index(1), Index(2), Index(0), Pick]), vec![Index(1), Index(2), Index(2)]);
+    assert_eq!(run(vec![Index(1), Index(2), Index(1), Pick]), vec![Index(1), Index(2), Index(1)]);
+    assert_eq!(run(vec![Index(1), Index(2), Index(3), Index(2), Pick]), vec![Index(1), Index(2), Index(3), Index(1)]);
+}
+
+#[test]
+pub fn roll() {
+    // 1 ROLL is SWAP and 2 ROLL is ROT
+    assert_eq!(run(vec![Index(1), Index(2), Index(1), Roll]), vec![Index(2), Index(1)]);
+    assert_eq!(run(vec![Index(1), Index(2), Index(3), Index(2), Roll]), vec![Index(2), Index(3), Index(1)]);
+    assert_eq!(run(vec![Index(1), Index(2), Index(0), Roll]), vec![Index(1), Index(2)]);
+}
+
+#[test]
+pub fn depth() {
+    assert_eq!(run(vec![Depth]), vec![Index(0)]);
+    assert_eq!(run(vec![Index(1), Index(2), Depth]), vec![Index(1), Index(2), Index(2)]);
+}
+
+#[test]
+pub fn dropn() {
+    assert_eq!(run(vec![Index(1), Index(2), Index(3), Index(2), DropN]), vec![Index(1)]);
+    assert_eq!(run(vec![Index(1), Index(0), DropN]), vec![Index(1)]);
+}
+
+#[test]
+pub fn stack_underflow() {
+    for script in vec![
+        vec![Index(1), Swap],
+        vec![Index(2), Index(1), Index(2), Pick],
+        vec![Index(2), Index(1), Index(2), Roll],
+        vec![Index(1), Index(2), DropN]
+    ] {
+        let mut machine = Machine::from(Script::from(script));
+        assert!(machine.execute(&NullIO).is_none());
+        match machine.pop() {
+            Some(Error(e)) => assert_eq!(e, "stack underflow"),
+            _ => panic!()
+        }
+
+        // the items that were there are left on the stack
+        assert_eq!(machine.pop(), Some(Index(1)));
+    }
+
+    for script in vec![
+        vec![Index(1), Index(-1), Pick],
+        vec![Index(1), Index(-1), Roll]
+    ] {
+        let mut machine = Machine::from(Script::from(script));
+        assert!(machine.execute(&NullIO).is_none());
+        match machine.pop() {
+            Some(Error(e)) => assert_eq!(e, "item count must not be negative"),
+            _ => panic!()
+        }
+        assert_eq!(machine.pop(), Some(Index(1)));
+    }
+}
+
+#[test]
+pub fn stack_ser_2() {
+    let script = Script::from(vec![
+        Index(1),
+        Index(2),
+        Index(3),
+        Swap,
+        Over,
+        Rot,
+        Nip,
+        Tuck,
+        Index(1),
+        Pick,
+        Index(2),
+        Roll,
+        Depth,
+        DropN
+    ]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""1 2 3 SWAP OVER ROT NIP TUCK 1 PICK 2 ROLL DEPTH DROPN""#);
+}
+
+#[test]
+pub fn stack_de_2() {
+    let s1 = Script::from(vec![
+        Index(1),
+        Index(2),
+        Index(3),
+        Swap,
+        Over,
+        Rot,
+        Nip,
+        Tuck,
+        Index(1),
+        Pick,
+        Index(2),
+        Roll,
+        Depth,
+        DropN
+    ]);
+    let s = r#""1 2 3 SWAP OVER ROT NIP TUCK 1 PICK 2 ROLL DEPTH DROPN""#;
+    let s2: Script<CCLang> = serde_json::from_str(s).unwrap();
+    assert_eq!(s1, s2);
+}
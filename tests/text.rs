@@ -0,0 +1,171 @@
+use bytes::{
+    BufMut,
+    BytesMut
+};
+use cclang::{
+    CCLang::{
+        self,
+        Binary,
+        Boolean,
+        Equal,
+        Error,
+        Text,
+        ToBytes,
+        ToText
+    },
+    Machine,
+    NullIO,
+    Script
+};
+#[cfg(feature = "hash-sha2")]
+use cclang::{
+    CCLang::{
+        Hash,
+        HashingId
+    },
+    Hashing
+};
+
+/* TEST DATA:
+text: hello world
+ hex: 68656c6c6f20776f726c64
+ sha256: b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9
+*/
+
+#[test]
+pub fn tobytes() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("68656c6c6f20776f726c64".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected binary
+        Binary(b.freeze()),
+
+        // convert the text to its UTF-8 bytes
+        Text("hello world".to_string()),
+        ToBytes,
+
+        // pop the converted binary and the expected binary and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[cfg(feature = "hash-sha2")]
+#[test]
+pub fn tobytes_hash() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected hash
+        Binary(b.freeze()),
+
+        // hash the text
+        Text("hello world".to_string()),
+        ToBytes,
+        HashingId(Hashing::SHA256),
+        Hash,
+
+        // pop the hash and the expected hash and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn totext() {
+    let mut b = BytesMut::new();
+    let data = hex::decode("68656c6c6f20776f726c64".to_string()).unwrap();
+    b.put_slice(&data);
+
+    let script = Script::from(vec![
+        // push the expected text
+        Text("hello world".to_string()),
+
+        // convert the UTF-8 bytes to text
+        Binary(b.freeze()),
+        ToText,
+
+        // pop the converted text and the expected text and compare
+        Equal
+    ]);
+    let mut machine = Machine::from(script);
+    let mut result = machine.execute(&NullIO).unwrap();
+
+    // should only be one item left on the stack
+    assert_eq!(result.size(), 1 as usize);
+
+    // the result should be a boolean with the value true
+    match result.pop() {
+        Some(Boolean(b)) => assert_eq!(b, true),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn totext_invalid_utf8() {
+    let mut b = BytesMut::new();
+    // a lone continuation byte
+    b.put_slice(&[0x68, 0x69, 0x80]);
+
+    let script = Script::from(vec![
+        Binary(b.freeze()),
+        ToText
+    ]);
+    let mut machine = Machine::from(script);
+
+    // the script stops at the TOTEXT
+    assert!(machine.execute(&NullIO).is_none());
+
+    // and leaves the error on the stack
+    match machine.pop() {
+        Some(Error(e)) => assert!(e.starts_with("binary is not valid UTF-8")),
+        _ => panic!()
+    }
+}
+
+#[test]
+pub fn text_ser_0() {
+    let script = Script::from(vec![
+        Text("hello".to_string()),
+        ToBytes,
+        ToText
+    ]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""hello TOBYTES TOTEXT""#);
+}
+
+#[test]
+pub fn text_de_0() {
+    let s1 = Script::from(vec![
+        Text("hello".to_string()),
+        ToBytes,
+        ToText
+    ]);
+    let s = r#""hello TOBYTES TOTEXT""#;
+    let s2: Script<CCLang> = serde_json::from_str(s).unwrap();
+    assert_eq!(s1, s2);
+}
@@ -0,0 +1,88 @@
+use cclang::{
+    outcome::run_with,
+    CCLang::{
+        self,
+        Boolean,
+        CheckTime,
+        Error,
+        GreaterThan,
+        Index,
+        Not,
+        Now
+    },
+    Context,
+    Machine,
+    NullIO,
+    Script,
+    ScriptOutcome
+};
+
+// 2021-01-01T00:00:00Z
+const T: isize = 1609459200;
+
+#[test]
+pub fn now() {
+    let mut machine = Machine::from(Script::from(vec![Now]));
+    let mut result = Context::new().now(T).execute(&mut machine, &NullIO).unwrap();
+    assert_eq!(result.pop(), Some(Index(T)));
+}
+
+#[test]
+pub fn checktime() {
+    let script = Script::from(vec![Index(T), CheckTime]);
+
+    // before, at and after the lock time
+    for (now, expected) in &[(T - 1, false), (T, true), (T + 1, true)] {
+        let mut machine = Machine::from(script.clone());
+        let mut result = Context::new().now(*now).execute(&mut machine, &NullIO).unwrap();
+        assert_eq!(result.pop(), Some(Boolean(*expected)));
+    }
+}
+
+#[test]
+pub fn valid_before() {
+    // a key that is only valid before T
+    let script = Script::from(vec![Index(T), CheckTime, Not]);
+    let mut ctx = Context::new();
+    ctx.now(T - 60);
+    let mut machine = Machine::from(script);
+    assert_eq!(run_with(&mut machine, &NullIO, true, &ctx), ScriptOutcome::Success);
+}
+
+#[test]
+pub fn no_time() {
+    let mut machine = Machine::from(Script::from(vec![Index(T), CheckTime]));
+    assert!(machine.execute(&NullIO).is_none());
+    assert_eq!(machine.pop(), Some(Error("the execution context has no time".to_string())));
+}
+
+#[test]
+pub fn context_not_shared() {
+    // each run sees only the context it is given
+    let mut machine = Machine::from(Script::from(vec![Now]));
+    let mut result = Context::new().now(T).execute(&mut machine, &NullIO).unwrap();
+    assert_eq!(result.pop(), Some(Index(T)));
+
+    let mut machine = Machine::from(Script::from(vec![Now]));
+    let mut result = Context::new().now(0).execute(&mut machine, &NullIO).unwrap();
+    assert_eq!(result.pop(), Some(Index(0)));
+
+    // and a machine run on its own has no time
+    let mut machine = Machine::from(Script::from(vec![Now]));
+    assert!(machine.execute(&NullIO).is_none());
+}
+
+#[test]
+pub fn time_ser_0() {
+    let script = Script::from(vec![Index(T), CheckTime, Now, Index(T), GreaterThan]);
+    let s = serde_json::to_string(&script).unwrap();
+    assert_eq!(s, r#""1609459200 CHECKTIME NOW 1609459200 >""#);
+}
+
+#[test]
+pub fn time_de_0() {
+    let s1 = Script::from(vec![Index(T), CheckTime, Now, Index(T), GreaterThan]);
+    let s = r#""1609459200 CHECKTIME NOW 1609459200 >""#;
+    let s2: Script<CCLang> = serde_json::from_str(s).unwrap();
+    assert_eq!(s1, s2);
+}